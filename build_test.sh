@@ -21,6 +21,8 @@ cargo check --no-default-features
 cargo check --no-default-features --features "serde"
 cargo check --no-default-features --features "std"
 cargo check --no-default-features --features "std, serde"
+cargo check --no-default-features --features "precomputed-srs"
+cargo check --no-default-features --features "header-builder"
 cargo check --target wasm32-unknown-unknown --no-default-features
 cargo check --target wasm32-unknown-unknown --no-default-features --features "serde"
 
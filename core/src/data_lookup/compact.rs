@@ -1,4 +1,4 @@
-use crate::{AppId, DataLookup};
+use crate::{data_lookup::ErrorReason, AppId, DataLookup};
 
 use codec::{Decode, Encode};
 use scale_info::TypeInfo;
@@ -35,11 +35,16 @@ where
 	}
 }
 
+/// `app_id` of the single index item of the versioned error encoding.
+const ERROR_ENCODING_V1: AppId = AppId(1);
+
 // If .size is 0, and index contains items then no commitment was generated
 // because of an error that occurred.
 //
-// This is just a temporary solution that will be replaced by a more
-// sofisticated one once we do to do the next header change.
+// Unknown errors keep the legacy encoding, `size: 0` and a single `(AppId(0), 0)` index item.
+// Errors with a known reason are encoded as `size: 0` and a single index item, whose `app_id` is
+// the version of the error encoding and whose `start` is the code of the `ErrorReason`. Any other
+// error lookup (`size: 0` with another index, or `size: u32::MAX`) has an unknown reason.
 //
 #[derive(Encode, Decode, TypeInfo, Debug, Clone)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
@@ -61,17 +66,34 @@ impl CompactDataLookup {
 		self.size == u32::MAX || (self.size == 0 && !self.index.is_empty())
 	}
 
+	/// Reason of the error, if this is an error lookup.
+	pub fn error_reason(&self) -> Option<ErrorReason> {
+		if !self.is_error() {
+			return None;
+		}
+		match self.index.as_slice() {
+			[item] if self.size == 0 && item.app_id == ERROR_ENCODING_V1 => {
+				Some(ErrorReason::from_code(item.start))
+			},
+			_ => Some(ErrorReason::Unknown),
+		}
+	}
+
 	// Data lookup is not valid if size is 0 and lookup index is not empty
-	fn new_error() -> Self {
+	fn new_error(reason: ErrorReason) -> Self {
+		let item = match reason {
+			ErrorReason::Unknown => DataLookupItem::new(AppId(0), 0),
+			reason => DataLookupItem::new(ERROR_ENCODING_V1, reason.code()),
+		};
 		Self {
 			size: 0,
-			index: [DataLookupItem::new(AppId(0), 0)].to_vec(),
+			index: [item].to_vec(),
 		}
 	}
 
 	pub fn from_data_lookup(lookup: &DataLookup) -> Self {
-		if lookup.is_error() {
-			return Self::new_error();
+		if let Some(reason) = lookup.error_reason() {
+			return Self::new_error(reason);
 		}
 
 		let index = lookup
@@ -25,6 +25,46 @@ pub enum Error {
 	DataEmptyOn(AppId),
 	#[error("Offset overflows")]
 	OffsetOverflows,
+	#[error("AppId {0} is duplicated")]
+	DuplicatedAppId(AppId),
+	#[error("Range of AppId {0} does not start after the previous range")]
+	InvalidRangeStart(AppId),
+	#[error("Size {0} does not cover the last range")]
+	InvalidSize(u32),
+}
+
+/// Reason why the header extension of a block could not be built.
+#[derive(Error, Debug, Clone, Copy, PartialEq, Eq, Encode, Decode, TypeInfo)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum ErrorReason {
+	/// Reason is not known, as in legacy error lookups.
+	#[error("Unknown error")]
+	Unknown,
+	/// Block data does not fit in the matrix.
+	#[error("Block is too big")]
+	BlockTooBig,
+	/// Commitments of the matrix could not be generated.
+	#[error("Commitments could not be generated")]
+	CommitmentFailure,
+}
+
+impl ErrorReason {
+	pub(crate) fn code(self) -> u32 {
+		match self {
+			ErrorReason::Unknown => 0,
+			ErrorReason::BlockTooBig => 1,
+			ErrorReason::CommitmentFailure => 2,
+		}
+	}
+
+	/// Codes unknown to this version are decoded as `Unknown`.
+	pub(crate) fn from_code(code: u32) -> Self {
+		match code {
+			1 => ErrorReason::BlockTooBig,
+			2 => ErrorReason::CommitmentFailure,
+			_ => ErrorReason::Unknown,
+		}
+	}
 }
 
 #[derive(PartialEq, Eq, Clone, Default)]
@@ -36,6 +76,7 @@ pub enum Error {
 #[cfg_attr(feature = "runtime", derive(RuntimeDebug))]
 pub struct DataLookup {
 	pub(crate) index: Vec<(AppId, DataLookupRange)>,
+	pub(crate) error: Option<ErrorReason>,
 }
 
 impl DataLookup {
@@ -48,15 +89,51 @@ impl DataLookup {
 	}
 
 	pub fn is_error(&self) -> bool {
-		self.is_empty() && !self.index.is_empty()
+		self.error.is_some()
+	}
+
+	/// Reason why the header extension could not be built, if this is an error lookup.
+	pub fn error_reason(&self) -> Option<ErrorReason> {
+		self.error
+	}
+
+	/// Iterates over the ranges of the applications, sorted by `AppId`.
+	pub fn iter(&self) -> impl Iterator<Item = (AppId, DataLookupRange)> + '_ {
+		self.index.iter().map(|(id, range)| (*id, range.clone()))
 	}
 
 	pub fn range_of(&self, app_id: AppId) -> Option<DataLookupRange> {
 		self.index
-			.iter()
-			.find(|(id, _)| *id == app_id)
-			.map(|(_, range)| range)
-			.cloned()
+			.binary_search_by_key(&app_id, |(id, _)| *id)
+			.ok()
+			.map(|pos| self.index[pos].1.clone())
+	}
+
+	/// Returns the application which owns the `chunk_index`-th chunk.
+	pub fn app_at(&self, chunk_index: u32) -> Option<AppId> {
+		// Ranges are contiguous, so the first range ending after the chunk contains it.
+		let pos = self
+			.index
+			.partition_point(|(_, range)| range.end <= chunk_index);
+		self.index.get(pos).map(|(id, _)| *id)
+	}
+
+	/// Returns the applications owning any chunk in `chunks`, sorted by `AppId`.
+	pub fn apps_in(&self, chunks: Range<u32>) -> impl Iterator<Item = AppId> + '_ {
+		let start = self
+			.index
+			.partition_point(|(_, range)| range.end <= chunks.start);
+		let end = self
+			.index
+			.partition_point(|(_, range)| range.start < chunks.end);
+		let apps = if chunks.is_empty() {
+			&[]
+		} else {
+			self.index.get(start..end).unwrap_or_default()
+		};
+		apps.iter()
+			.filter(|(_, range)| !range.is_empty())
+			.map(|(id, _)| *id)
 	}
 
 	pub fn projected_range_of(&self, app_id: AppId, chunk_size: u32) -> Option<DataLookupRange> {
@@ -119,18 +196,27 @@ impl DataLookup {
 			})
 			.collect::<Result<_, _>>()?;
 
-		Ok(Self { index })
+		Ok(Self { index, error: None })
 	}
 
 	/// This function is used a block contains no data submissions.
 	pub fn new_empty() -> Self {
-		Self { index: Vec::new() }
+		Self {
+			index: Vec::new(),
+			error: None,
+		}
 	}
 
 	/// This function is only used when something has gone wrong during header extension building
 	pub fn new_error() -> Self {
+		Self::new_error_with_reason(ErrorReason::Unknown)
+	}
+
+	/// Like [`Self::new_error`], recording why the header extension could not be built.
+	pub fn new_error_with_reason(reason: ErrorReason) -> Self {
 		Self {
 			index: vec![(AppId(0), 0..0)],
+			error: Some(reason),
 		}
 	}
 }
@@ -139,12 +225,14 @@ impl TryFrom<CompactDataLookup> for DataLookup {
 	type Error = Error;
 
 	fn try_from(compacted: CompactDataLookup) -> Result<Self, Self::Error> {
-		if compacted.is_error() {
-			return Ok(DataLookup::new_error());
+		if let Some(reason) = compacted.error_reason() {
+			return Ok(DataLookup::new_error_with_reason(reason));
 		}
 
-		let mut offset = 0;
+		// Items follow the implicit range of `AppId(0)`, which is the only one that can be empty.
+		// It is kept even when empty, so `range_of(AppId(0))` is `Some(0..0)` as it always was.
 		let mut prev_id = AppId(0);
+		let mut prev_start = 0;
 		let mut index = Vec::with_capacity(
 			compacted
 				.index
@@ -154,20 +242,32 @@ impl TryFrom<CompactDataLookup> for DataLookup {
 		);
 
 		for c_item in compacted.index {
-			index.push((prev_id, offset..c_item.start));
+			ensure!(
+				c_item.app_id != prev_id,
+				Error::DuplicatedAppId(c_item.app_id)
+			);
+			ensure!(c_item.app_id > prev_id, Error::DataNotSorted);
+			ensure!(
+				c_item.start > prev_start || index.is_empty(),
+				Error::InvalidRangeStart(c_item.app_id)
+			);
+
+			index.push((prev_id, prev_start..c_item.start));
 			prev_id = c_item.app_id;
-			offset = c_item.start;
+			prev_start = c_item.start;
 		}
 
-		let last_range = offset..compacted.size;
+		let last_range = prev_start..compacted.size;
 		if !last_range.is_empty() {
 			index.push((prev_id, last_range));
+		} else {
+			ensure!(
+				prev_id == AppId(0) && compacted.size == 0,
+				Error::InvalidSize(compacted.size)
+			);
 		}
 
-		let lookup = DataLookup { index };
-		ensure!(lookup.len() == compacted.size, Error::DataNotSorted);
-
-		Ok(lookup)
+		Ok(DataLookup { index, error: None })
 	}
 }
 
@@ -201,6 +301,7 @@ impl TypeInfo for DataLookup {
 #[cfg(test)]
 mod test {
 	use super::*;
+	use proptest::{collection, prelude::*};
 	use test_case::test_case;
 
 	#[test_case( vec![(0, 15), (1, 20), (2, 150)] => Ok(vec![(0,0..15),(1, 15..35), (2, 35..185)]); "Valid case")]
@@ -257,4 +358,184 @@ mod test {
 
 		assert_eq!(lookup, expanded_lookup);
 	}
+
+	#[test_case( vec![(0, 15), (1, 20), (5, 150)], 0 => Some(0..15); "Zero AppId")]
+	#[test_case( vec![(0, 15), (1, 20), (5, 150)], 5 => Some(35..185); "Last AppId")]
+	#[test_case( vec![(0, 15), (1, 20), (5, 150)], 3 => None; "Missing AppId")]
+	#[test_case( vec![] , 0 => None; "Empty")]
+	fn range_of(id_lens: Vec<(u32, usize)>, app_id: u32) -> Option<DataLookupRange> {
+		let lookup = DataLookup::from_id_and_len_iter(id_lens.into_iter()).unwrap();
+		lookup.range_of(AppId(app_id))
+	}
+
+	#[test_case( 0 => Some(0); "First chunk")]
+	#[test_case( 14 => Some(0); "Last chunk of first app")]
+	#[test_case( 15 => Some(1); "First chunk of second app")]
+	#[test_case( 184 => Some(5); "Last chunk")]
+	#[test_case( 185 => None; "Out of range")]
+	fn app_at(chunk_index: u32) -> Option<u32> {
+		let lookup =
+			DataLookup::from_id_and_len_iter([(0u32, 15u32), (1, 20), (5, 150)].into_iter())
+				.unwrap();
+		lookup.app_at(chunk_index).map(|id| id.0)
+	}
+
+	#[test_case( 0..15 => vec![0]; "First app")]
+	#[test_case( 14..16 => vec![0, 1]; "Overlapping two apps")]
+	#[test_case( 10..200 => vec![0, 1, 5]; "All apps")]
+	#[test_case( 35..36 => vec![5]; "Single chunk")]
+	#[test_case( 20..20 => Vec::<u32>::new(); "Empty range")]
+	#[test_case( 185..190 => Vec::<u32>::new(); "Out of range")]
+	fn apps_in(chunks: Range<u32>) -> Vec<u32> {
+		let lookup =
+			DataLookup::from_id_and_len_iter([(0u32, 15u32), (1, 20), (5, 150)].into_iter())
+				.unwrap();
+		lookup.apps_in(chunks).map(|id| id.0).collect()
+	}
+
+	#[test]
+	fn lookup_skips_empty_ranges() {
+		// The compact lookup of a block without `AppId(0)` data expands to an empty range for it.
+		let compact = CompactDataLookup::new(30, vec![(1u32, 0u32).into(), (2u32, 10u32).into()]);
+		let lookup = DataLookup::try_from(compact).unwrap();
+
+		assert_eq!(lookup.app_at(0), Some(AppId(1)));
+		assert_eq!(lookup.app_at(10), Some(AppId(2)));
+		assert_eq!(
+			lookup.apps_in(0..11).collect::<Vec<_>>(),
+			vec![AppId(1), AppId(2)]
+		);
+		assert_eq!(
+			lookup.iter().collect::<Vec<_>>(),
+			vec![(AppId(0), 0..0), (AppId(1), 0..10), (AppId(2), 10..30)]
+		);
+	}
+
+	#[test_case( 185, vec![(1, 15), (2, 35)] => Ok(vec![(0, 0..15), (1, 15..35), (2, 35..185)]); "Valid case")]
+	#[test_case( 20, vec![(1, 0)] => Ok(vec![(0, 0..0), (1, 0..20)]); "Without AppId zero")]
+	#[test_case( 0, vec![] => Ok(vec![]); "Empty")]
+	#[test_case( 185, vec![(2, 15), (1, 35)] => Err(Error::DataNotSorted); "Unsorted AppIds")]
+	#[test_case( 185, vec![(1, 15), (1, 35)] => Err(Error::DuplicatedAppId(AppId(1))); "Duplicated AppId")]
+	#[test_case( 185, vec![(0, 15)] => Err(Error::DuplicatedAppId(AppId(0))); "Explicit zero AppId")]
+	#[test_case( 185, vec![(1, 35), (2, 15)] => Err(Error::InvalidRangeStart(AppId(2))); "Inverted range")]
+	#[test_case( 185, vec![(1, 15), (2, 15)] => Err(Error::InvalidRangeStart(AppId(2))); "Empty range")]
+	#[test_case( 35, vec![(1, 15), (2, 35)] => Err(Error::InvalidSize(35)); "Empty last range")]
+	#[test_case( 20, vec![(1, 15), (2, 35)] => Err(Error::InvalidSize(20)); "Size before last start")]
+	fn strict_decoding(
+		size: u32,
+		items: Vec<(u32, u32)>,
+	) -> Result<Vec<(u32, DataLookupRange)>, Error> {
+		let compact = CompactDataLookup::new(size, items.into_iter().map(Into::into).collect());
+		let encoded = compact.encode();
+		assert_eq!(
+			DataLookup::decode(&mut encoded.as_slice()).is_ok(),
+			DataLookup::try_from(compact.clone()).is_ok()
+		);
+
+		DataLookup::try_from(compact)
+			.map(|lookup| lookup.iter().map(|(id, range)| (id.0, range)).collect())
+	}
+
+	#[test_case( CompactDataLookup::new(0, vec![(0u32, 0u32).into()]) ; "Legacy empty size")]
+	#[test_case( CompactDataLookup::new(u32::MAX, vec![]) ; "Legacy max size")]
+	#[test_case( CompactDataLookup::new(0, vec![(0u32, 7u32).into(), (2u32, 3u32).into()]) ; "Legacy non-standard index")]
+	fn legacy_errors_decode(compact: CompactDataLookup) {
+		let lookup = DataLookup::decode(&mut compact.encode().as_slice()).unwrap();
+		assert!(lookup.is_error());
+		assert_eq!(lookup.error_reason(), Some(ErrorReason::Unknown));
+		assert_eq!(lookup, DataLookup::new_error());
+	}
+
+	#[test_case( ErrorReason::Unknown ; "Unknown")]
+	#[test_case( ErrorReason::BlockTooBig ; "Block too big")]
+	#[test_case( ErrorReason::CommitmentFailure ; "Commitment failure")]
+	fn error_reason_conversions(reason: ErrorReason) {
+		let lookup = DataLookup::new_error_with_reason(reason);
+		assert!(lookup.is_error());
+		assert!(lookup.is_empty());
+
+		let compact = CompactDataLookup::from_data_lookup(&lookup);
+		// Legacy decoders still see an error lookup.
+		assert_eq!(compact.size, 0);
+		assert!(!compact.index.is_empty());
+
+		let decoded = DataLookup::decode(&mut lookup.encode().as_slice()).unwrap();
+		assert_eq!(decoded.error_reason(), Some(reason));
+
+		let json = serde_json::to_string(&lookup).unwrap();
+		let from_json = serde_json::from_str::<DataLookup>(&json).unwrap();
+		assert_eq!(from_json.error_reason(), Some(reason));
+	}
+
+	#[test]
+	fn unknown_error_keeps_legacy_encoding() {
+		// `size: 0` and a single `(AppId(0), 0)` item, all compact encoded.
+		assert_eq!(DataLookup::new_error().encode(), [0, 4, 0, 0]);
+		let compact = CompactDataLookup::from_data_lookup(&DataLookup::new_error());
+		assert_eq!(
+			compact.encode(),
+			CompactDataLookup::new(0, vec![(0u32, 0u32).into()]).encode()
+		);
+	}
+
+	#[test]
+	fn unknown_error_reason_code() {
+		let compact = CompactDataLookup::new(0, vec![(1u32, 42u32).into()]);
+		let lookup = DataLookup::try_from(compact).unwrap();
+		assert_eq!(lookup.error_reason(), Some(ErrorReason::Unknown));
+
+		let lookup = DataLookup::from_id_and_len_iter([(0u32, 15u32)].into_iter()).unwrap();
+		assert_eq!(lookup.error_reason(), None);
+	}
+
+	fn compact_lookup_strategy() -> impl Strategy<Value = CompactDataLookup> {
+		let items = collection::vec((0u32..8, 0u32..64), 0..6);
+		let sorted_items = items.clone().prop_map(|mut items| {
+			items.sort();
+			items
+		});
+		let size = prop_oneof![Just(0), Just(u32::MAX), 0u32..80];
+		(size, prop_oneof![items, sorted_items]).prop_map(|(size, items)| {
+			CompactDataLookup::new(size, items.into_iter().map(Into::into).collect())
+		})
+	}
+
+	proptest! {
+	#[test]
+	fn decode_encode_is_identity(compact in compact_lookup_strategy()) {
+		if let Ok(lookup) = DataLookup::decode(&mut compact.encode().as_slice()) {
+			let encoded = lookup.encode();
+			prop_assert_eq!(DataLookup::decode(&mut encoded.as_slice()), Ok(lookup.clone()));
+
+			// Decoded lookups are sorted, contiguous and without empty ranges, except `AppId(0)`.
+			let ranges = lookup.iter().collect::<Vec<_>>();
+			if !lookup.is_error() {
+				prop_assert!(ranges.iter().all(|(id, range)| !range.is_empty() || *id == AppId(0)));
+				prop_assert!(ranges.windows(2).all(|w| w[0].0 < w[1].0 && w[0].1.end == w[1].1.start));
+				prop_assert_eq!(ranges.first().map_or(0, |(_, range)| range.start), 0);
+			}
+		}
+	}
+
+	#[test]
+	fn decode_arbitrary_bytes(bytes in collection::vec(any::<u8>(), 0..64)) {
+		if let Ok(lookup) = DataLookup::decode(&mut bytes.as_slice()) {
+			let encoded = lookup.encode();
+			prop_assert_eq!(DataLookup::decode(&mut encoded.as_slice()), Ok(lookup));
+		}
+	}
+
+	#[test]
+	fn valid_lookups_round_trip(lens in collection::vec((1u32..8, 1u32..32), 0..8)) {
+		let mut id_lens = lens;
+		id_lens.sort_by_key(|(id, _)| *id);
+		id_lens.dedup_by_key(|(id, _)| *id);
+		let lookup = DataLookup::from_id_and_len_iter(id_lens.into_iter()).unwrap();
+		let decoded = DataLookup::decode(&mut lookup.encode().as_slice()).unwrap();
+		// Decoding only adds the empty range of `AppId(0)`.
+		let non_empty = decoded.iter().filter(|(_, range)| !range.is_empty()).collect::<Vec<_>>();
+		prop_assert_eq!(non_empty, lookup.iter().collect::<Vec<_>>());
+		prop_assert_eq!(decoded.encode(), lookup.encode());
+	}
+	}
 }
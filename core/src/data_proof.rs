@@ -1,10 +1,14 @@
 use bounded_collections::BoundedVec;
 use bounded_collections::ConstU32;
-use codec::{Decode, Encode};
-use derive_more::Constructor;
+use codec::{Decode, Encode, MaxEncodedLen};
+use core::str::FromStr;
+use derive_more::{Constructor, From, Into};
 use primitive_types::H256;
 use scale_info::TypeInfo;
 use sp_std::vec::Vec;
+use thiserror_no_std::Error;
+
+use crate::{ensure, keccak256_concat};
 
 #[cfg(feature = "runtime")]
 use binary_merkle_tree::MerkleProof;
@@ -17,27 +21,113 @@ pub const BOUNDED_DATA_MAX_LENGTH: u32 = 102_400;
 /// Maximum size of data allowed in the bridge
 pub type BoundedData = BoundedVec<u8, ConstU32<BOUNDED_DATA_MAX_LENGTH>>;
 
+pub mod bridge;
 pub mod message;
 
+pub use bridge::{BridgeTree, BridgeTreeBuilder};
 pub use message::{AddressedMessage, Message, MessageType};
 
+/// Unique Tx identifier based on its block number and index.
+///
+/// The block number is stored in the high 32 bits, so identifiers are ordered by block first.
+#[derive(
+	Clone,
+	Copy,
+	Debug,
+	PartialEq,
+	Eq,
+	PartialOrd,
+	Ord,
+	Hash,
+	Default,
+	Encode,
+	Decode,
+	TypeInfo,
+	MaxEncodedLen,
+	From,
+	Into,
+)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct TxUid(#[codec(compact)] pub u64);
+
+impl TxUid {
+	pub fn new(block: u32, tx_index: u32) -> Self {
+		let mut buf = [0u8; 8];
+		buf[..4].copy_from_slice(&block.to_be_bytes());
+		buf[4..].copy_from_slice(&tx_index.to_be_bytes());
+		Self(u64::from_be_bytes(buf))
+	}
+
+	/// Block number and index of the Tx.
+	pub fn deconstruct(&self) -> (u32, u32) {
+		const SLICE_ERR: &str = "Valid slice .qed";
+
+		let id: [u8; 8] = self.0.to_be_bytes();
+		let block = u32::from_be_bytes(id[..4].try_into().expect(SLICE_ERR));
+		let tx_index = u32::from_be_bytes(id[4..].try_into().expect(SLICE_ERR));
+
+		(block, tx_index)
+	}
+
+	pub fn block(&self) -> u32 {
+		self.deconstruct().0
+	}
+
+	pub fn tx_index(&self) -> u32 {
+		self.deconstruct().1
+	}
+}
+
+impl From<(u32, u32)> for TxUid {
+	fn from((block, tx_index): (u32, u32)) -> Self {
+		Self::new(block, tx_index)
+	}
+}
+
+impl From<TxUid> for (u32, u32) {
+	fn from(uid: TxUid) -> Self {
+		uid.deconstruct()
+	}
+}
+
+impl core::fmt::Display for TxUid {
+	fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+		let (block, tx_index) = self.deconstruct();
+		write!(f, "{block}:{tx_index}")
+	}
+}
+
+#[derive(Error, Debug, Clone, Copy, PartialEq, Eq)]
+#[error("Tx UID must be formatted as `block:index`")]
+pub struct TxUidParseError;
+
+impl FromStr for TxUid {
+	type Err = TxUidParseError;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		let (block, tx_index) = s.split_once(':').ok_or(TxUidParseError)?;
+		let block = block.parse().map_err(|_| TxUidParseError)?;
+		let tx_index = tx_index.parse().map_err(|_| TxUidParseError)?;
+		Ok(Self::new(block, tx_index))
+	}
+}
+
+impl TryFrom<&str> for TxUid {
+	type Error = TxUidParseError;
+
+	fn try_from(s: &str) -> Result<Self, Self::Error> {
+		s.parse()
+	}
+}
+
 /// Unique Tx identifier based on its block number and index.
 pub fn tx_uid(block: u32, tx_index: u32) -> u64 {
-	let mut buf = [0u8; 8];
-	buf[..4].copy_from_slice(&block.to_be_bytes());
-	buf[4..].copy_from_slice(&tx_index.to_be_bytes());
-	u64::from_be_bytes(buf)
+	TxUid::new(block, tx_index).0
 }
 
 /// Deconstructs the Unique Tx identifier into its block number and index.
 pub fn tx_uid_deconstruct(uid: u64) -> (u32, u32) {
-	const SLICE_ERR: &str = "Valid slice .qed";
-
-	let id: [u8; 8] = uid.to_be_bytes();
-	let block = u32::from_be_bytes(id[..4].try_into().expect(SLICE_ERR));
-	let tx_index = u32::from_be_bytes(id[4..].try_into().expect(SLICE_ERR));
-
-	(block, tx_index)
+	TxUid(uid).deconstruct()
 }
 
 #[derive(Clone, Debug, Encode, Decode, Constructor, TypeInfo)]
@@ -67,6 +157,21 @@ pub struct TxDataRoots {
 	pub bridge_root: H256,
 }
 
+impl TxDataRoots {
+	/// Returns `true` if `data_root` is the hash of `blob_root` and `bridge_root`.
+	pub fn is_valid(&self) -> bool {
+		self.data_root == keccak256_concat!(self.blob_root, self.bridge_root)
+	}
+
+	/// Root of the given sub-trie.
+	pub fn sub_root(&self, sub_trie: &SubTrie) -> H256 {
+		match sub_trie {
+			SubTrie::DataSubmit => self.blob_root,
+			SubTrie::Bridge => self.bridge_root,
+		}
+	}
+}
+
 #[cfg(feature = "runtime")]
 impl TxDataRoots {
 	pub fn new(submitted: H256, bridged: H256) -> Self {
@@ -84,6 +189,148 @@ impl TxDataRoots {
 	}
 }
 
+#[derive(Error, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProofError {
+	#[error("Proof is for an empty tree")]
+	EmptyTree,
+	#[error("Leaf index {leaf_index} is out of a tree of {number_of_leaves} leaves")]
+	LeafIndexOutOfBounds {
+		leaf_index: u32,
+		number_of_leaves: u32,
+	},
+	#[error("Proof has {actual} items, expected {expected}")]
+	InvalidProofLength { expected: usize, actual: usize },
+	#[error("Leaf does not match the proven leaf")]
+	LeafMismatch,
+	#[error("Computed root does not match the sub-trie root")]
+	InvalidSubRoot,
+	#[error("Data root does not match the blob and bridge roots")]
+	InvalidDataRoot,
+	#[error("Proof has no leaves")]
+	NoLeaves,
+	#[error("Leaf indices are not strictly increasing")]
+	UnsortedLeaves,
+	#[error("Leaf has {0} bytes, expected 32")]
+	InvalidLeafLength(usize),
+	#[error("Tree has {0} leaves, more than a proof can index")]
+	TooManyLeaves(usize),
+}
+
+/// Checks that `leaf_index` is a leaf of a tree of `number_of_leaves` leaves.
+pub(crate) fn ensure_leaf_index(leaf_index: u32, number_of_leaves: u32) -> Result<(), ProofError> {
+	ensure!(number_of_leaves != 0, ProofError::EmptyTree);
+	ensure!(
+		leaf_index < number_of_leaves,
+		ProofError::LeafIndexOutOfBounds {
+			leaf_index,
+			number_of_leaves
+		}
+	);
+	Ok(())
+}
+
+/// Number of inner nodes needed to prove the `leaf_index`-th leaf of a tree of
+/// `number_of_leaves` leaves. The last node of an odd layer is promoted without a sibling.
+pub(crate) fn proof_len(leaf_index: u32, number_of_leaves: u32) -> usize {
+	let (mut position, mut width, mut len) = (leaf_index, number_of_leaves, 0usize);
+	while width > 1 {
+		if position % 2 == 1 || position.saturating_add(1) < width {
+			len = len.saturating_add(1);
+		}
+		position /= 2;
+		width = width.div_ceil(2);
+	}
+	len
+}
+
+/// Computes the root of the tree from the hash of the `leaf_index`-th leaf and its proof,
+/// the same way `binary-merkle-tree` does.
+pub(crate) fn merkle_root(
+	leaf_hash: H256,
+	proof: &[H256],
+	leaf_index: u32,
+	number_of_leaves: u32,
+) -> H256 {
+	let mut position = leaf_index;
+	let mut width = number_of_leaves;
+	proof.iter().fold(leaf_hash, |node, sibling| {
+		let parent = if position % 2 == 1 || position.saturating_add(1) == width {
+			keccak256_concat!(sibling, node)
+		} else {
+			keccak256_concat!(node, sibling)
+		};
+		position /= 2;
+		width = width.div_ceil(2);
+		parent
+	})
+}
+
+/// Computes the root of the tree from known nodes of its first layer, sorted by their position.
+/// Missing siblings are requested from `sibling` by their layer and position, in the order they
+/// are needed, layer by layer.
+fn multi_merkle_root(
+	mut nodes: Vec<(u32, H256)>,
+	number_of_leaves: u32,
+	mut sibling: impl FnMut(usize, u32) -> H256,
+) -> H256 {
+	let (mut width, mut layer) = (number_of_leaves, 0usize);
+	while width > 1 {
+		let mut parents = Vec::with_capacity(nodes.len());
+		let mut known = nodes.into_iter().peekable();
+		while let Some((position, node)) = known.next() {
+			let right = position.saturating_add(1);
+			let parent = if position % 2 == 1 {
+				// The left sibling would have been consumed with the node otherwise.
+				keccak256_concat!(sibling(layer, position.saturating_sub(1)), node)
+			} else if right == width {
+				node
+			} else {
+				let right_node = match known.next_if(|(p, _)| *p == right) {
+					Some((_, right_node)) => right_node,
+					None => sibling(layer, right),
+				};
+				keccak256_concat!(node, right_node)
+			};
+			parents.push((position / 2, parent));
+		}
+		nodes = parents;
+		width = width.div_ceil(2);
+		layer = layer.saturating_add(1);
+	}
+	nodes.first().map(|(_, root)| *root).unwrap_or_default()
+}
+
+/// Layers of the tree over `leaf_hashes`, from leaves to the root.
+pub(crate) fn merkle_layers(leaf_hashes: Vec<H256>) -> Vec<Vec<H256>> {
+	let mut layers = sp_std::vec![leaf_hashes];
+	while let Some(layer) = layers.last().filter(|layer| layer.len() > 1) {
+		let parents = layer
+			.chunks(2)
+			.map(|pair| match pair {
+				[left, right] => keccak256_concat!(left, right),
+				[node] => *node,
+				_ => unreachable!("Chunks have one or two nodes .qed"),
+			})
+			.collect();
+		layers.push(parents);
+	}
+	layers
+}
+
+/// Siblings of the nodes on the path from the `leaf_index`-th leaf to the root, skipping layers
+/// where the node is promoted.
+pub(crate) fn merkle_path(layers: &[Vec<H256>], leaf_index: u32) -> Vec<H256> {
+	let mut position = leaf_index as usize;
+	let mut proof = Vec::new();
+	for layer in layers.iter().take(layers.len().saturating_sub(1)) {
+		if let Some(sibling) = layer.get(position ^ 1) {
+			proof.push(*sibling);
+		}
+		position /= 2;
+	}
+	proof
+}
+
 /// Wrapper of `binary-merkle-tree::MerkleProof` with codec support.
 #[derive(Clone, Debug, PartialEq, Eq, Encode, Decode, Default, TypeInfo)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
@@ -146,3 +393,428 @@ impl DataProof {
 		}
 	}
 }
+
+impl DataProof {
+	/// Verifies that `leaf` is the proven leaf of `sub_trie`, and that sub-trie roots are part of
+	/// the data root. It only requires Keccak 256, so it can be used without the runtime.
+	///
+	/// `leaf` is the hash of the submitted data for [`SubTrie::DataSubmit`], and the encoded
+	/// message for [`SubTrie::Bridge`].
+	pub fn verify(&self, sub_trie: SubTrie, leaf: &[u8]) -> Result<(), ProofError> {
+		ensure_leaf_index(self.leaf_index, self.number_of_leaves)?;
+		let expected = proof_len(self.leaf_index, self.number_of_leaves);
+		ensure!(
+			self.proof.len() == expected,
+			ProofError::InvalidProofLength {
+				expected,
+				actual: self.proof.len()
+			}
+		);
+
+		let leaf_hash = keccak256_concat!(leaf);
+		let proven_leaf = match sub_trie {
+			SubTrie::DataSubmit if leaf.len() == H256::len_bytes() => H256::from_slice(leaf),
+			SubTrie::DataSubmit => return Err(ProofError::LeafMismatch),
+			SubTrie::Bridge => leaf_hash,
+		};
+		ensure!(proven_leaf == self.leaf, ProofError::LeafMismatch);
+
+		let root = merkle_root(
+			leaf_hash,
+			&self.proof,
+			self.leaf_index,
+			self.number_of_leaves,
+		);
+		ensure!(
+			root == self.roots.sub_root(&sub_trie),
+			ProofError::InvalidSubRoot
+		);
+		ensure!(self.roots.is_valid(), ProofError::InvalidDataRoot);
+
+		Ok(())
+	}
+}
+
+/// Proof of several leaves of the same sub-trie.
+///
+/// Inner nodes shared by the paths of the leaves, or computable from the proven leaves, are only
+/// included once.
+#[derive(Clone, Debug, PartialEq, Eq, Encode, Decode, Default, TypeInfo)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
+pub struct MultiDataProof {
+	pub roots: TxDataRoots,
+	/// Inner node hashes necessary to reconstruct the root hash given the leaf hashes.
+	///
+	/// They are ordered layer by layer, from the leaves up, and by position within each layer.
+	pub proof: Vec<H256>,
+	/// Number of leaves in the original tree.
+	#[codec(compact)]
+	pub number_of_leaves: u32,
+	/// Proven leaves as `(leaf_index, leaf)`, sorted by their index.
+	pub leaves: Vec<(u32, H256)>,
+}
+
+impl MultiDataProof {
+	/// Creates the proof of the `leaf_indices` leaves of the sub-trie built from `leaves`.
+	///
+	/// `leaves` are hashes of the submitted data for [`SubTrie::DataSubmit`], and the encoded
+	/// messages for [`SubTrie::Bridge`].
+	pub fn new<L: AsRef<[u8]>>(
+		sub_trie: SubTrie,
+		roots: TxDataRoots,
+		leaves: &[L],
+		leaf_indices: &[u32],
+	) -> Result<Self, ProofError> {
+		let number_of_leaves =
+			u32::try_from(leaves.len()).map_err(|_| ProofError::TooManyLeaves(leaves.len()))?;
+		ensure!(!leaf_indices.is_empty(), ProofError::NoLeaves);
+		let mut leaf_indices = leaf_indices.to_vec();
+		leaf_indices.sort_unstable();
+		leaf_indices.dedup();
+
+		let mut proven = Vec::with_capacity(leaf_indices.len());
+		for leaf_index in leaf_indices {
+			ensure_leaf_index(leaf_index, number_of_leaves)?;
+			let leaf = leaves[leaf_index as usize].as_ref();
+			let leaf = match sub_trie {
+				SubTrie::DataSubmit if leaf.len() == H256::len_bytes() => H256::from_slice(leaf),
+				SubTrie::DataSubmit => return Err(ProofError::InvalidLeafLength(leaf.len())),
+				SubTrie::Bridge => keccak256_concat!(leaf),
+			};
+			proven.push((leaf_index, leaf));
+		}
+
+		let layers = merkle_layers(leaves.iter().map(|leaf| keccak256_concat!(leaf)).collect());
+		let mut proof = Vec::new();
+		let proven_hashes = Self::leaf_hashes(&sub_trie, &proven);
+		multi_merkle_root(proven_hashes, number_of_leaves, |layer, position| {
+			let node = layers
+				.get(layer)
+				.and_then(|layer| layer.get(position as usize))
+				.copied()
+				.unwrap_or_default();
+			proof.push(node);
+			node
+		});
+
+		Ok(Self {
+			roots,
+			proof,
+			number_of_leaves,
+			leaves: proven,
+		})
+	}
+
+	/// Hashes of the proven leaves in the tree.
+	fn leaf_hashes(sub_trie: &SubTrie, leaves: &[(u32, H256)]) -> Vec<(u32, H256)> {
+		leaves
+			.iter()
+			.map(|(leaf_index, leaf)| {
+				let hash = match sub_trie {
+					SubTrie::DataSubmit => keccak256_concat!(leaf),
+					SubTrie::Bridge => *leaf,
+				};
+				(*leaf_index, hash)
+			})
+			.collect()
+	}
+
+	/// Verifies that all proven leaves belong to `sub_trie`, and that sub-trie roots are part of
+	/// the data root. Like [`DataProof::verify`], it only requires Keccak 256.
+	pub fn verify(&self, sub_trie: SubTrie) -> Result<(), ProofError> {
+		let (last, _) = self.leaves.last().ok_or(ProofError::NoLeaves)?;
+		ensure!(
+			self.leaves.windows(2).all(|pair| pair[0].0 < pair[1].0),
+			ProofError::UnsortedLeaves
+		);
+		ensure_leaf_index(*last, self.number_of_leaves)?;
+
+		let mut proof = self.proof.iter();
+		let mut expected = 0usize;
+		let leaf_hashes = Self::leaf_hashes(&sub_trie, &self.leaves);
+		let root = multi_merkle_root(leaf_hashes, self.number_of_leaves, |_, _| {
+			expected = expected.saturating_add(1);
+			proof.next().copied().unwrap_or_default()
+		});
+		ensure!(
+			self.proof.len() == expected,
+			ProofError::InvalidProofLength {
+				expected,
+				actual: self.proof.len()
+			}
+		);
+		ensure!(
+			root == self.roots.sub_root(&sub_trie),
+			ProofError::InvalidSubRoot
+		);
+		ensure!(self.roots.is_valid(), ProofError::InvalidDataRoot);
+
+		Ok(())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::Keccak256;
+	use binary_merkle_tree::{merkle_proof, merkle_root};
+
+	fn roots(blob_root: H256, bridge_root: H256) -> TxDataRoots {
+		TxDataRoots {
+			data_root: keccak256_concat!(blob_root, bridge_root),
+			blob_root,
+			bridge_root,
+		}
+	}
+
+	#[test]
+	fn verify_all_leaves() {
+		for n in 1..=9 {
+			let blobs = (0..n)
+				.map(|i| H256::repeat_byte(i as u8))
+				.collect::<Vec<_>>();
+			let messages = (0..n).map(|i| vec![i as u8; 40]).collect::<Vec<_>>();
+
+			for index in 0..n {
+				let blob_proof = merkle_proof::<Keccak256, _, _>(blobs.iter(), index);
+				let bridge_proof = merkle_proof::<Keccak256, _, _>(messages.iter(), index);
+				let roots = roots(blob_proof.root, bridge_proof.root);
+
+				let proof = DataProof {
+					roots,
+					proof: blob_proof.proof,
+					number_of_leaves: blob_proof.number_of_leaves as u32,
+					leaf_index: blob_proof.leaf_index as u32,
+					leaf: blobs[index],
+				};
+				assert_eq!(
+					proof.verify(SubTrie::DataSubmit, blobs[index].as_bytes()),
+					Ok(())
+				);
+
+				let proof = DataProof {
+					roots,
+					proof: bridge_proof.proof,
+					number_of_leaves: bridge_proof.number_of_leaves as u32,
+					leaf_index: bridge_proof.leaf_index as u32,
+					leaf: keccak256_concat!(messages[index]),
+				};
+				assert_eq!(proof.verify(SubTrie::Bridge, &messages[index]), Ok(()));
+			}
+		}
+	}
+
+	#[test]
+	fn verify_rejects_invalid_proofs() {
+		let blobs = (0..5u8).map(H256::repeat_byte).collect::<Vec<_>>();
+		let blob_proof = merkle_proof::<Keccak256, _, _>(blobs.iter(), 2);
+		let proof = DataProof {
+			roots: roots(blob_proof.root, H256::zero()),
+			proof: blob_proof.proof,
+			number_of_leaves: 5,
+			leaf_index: 2,
+			leaf: blobs[2],
+		};
+		let leaf = blobs[2].as_bytes();
+		assert_eq!(proof.verify(SubTrie::DataSubmit, leaf), Ok(()));
+
+		let invalid = |f: &dyn Fn(&mut DataProof)| {
+			let mut invalid = proof.clone();
+			f(&mut invalid);
+			invalid.verify(SubTrie::DataSubmit, leaf).unwrap_err()
+		};
+		assert_eq!(invalid(&|p| p.number_of_leaves = 0), ProofError::EmptyTree);
+		assert_eq!(
+			invalid(&|p| p.leaf_index = 5),
+			ProofError::LeafIndexOutOfBounds {
+				leaf_index: 5,
+				number_of_leaves: 5
+			}
+		);
+		assert_eq!(
+			invalid(&|p| p.number_of_leaves = 3),
+			ProofError::InvalidProofLength {
+				expected: 1,
+				actual: 3
+			}
+		);
+		assert_eq!(invalid(&|p| p.leaf = blobs[1]), ProofError::LeafMismatch);
+		assert_eq!(
+			invalid(&|p| p.proof[0] = H256::zero()),
+			ProofError::InvalidSubRoot
+		);
+		assert_eq!(
+			invalid(&|p| p.roots.data_root = H256::zero()),
+			ProofError::InvalidDataRoot
+		);
+		assert_eq!(
+			proof.verify(SubTrie::Bridge, leaf),
+			Err(ProofError::LeafMismatch)
+		);
+	}
+
+	#[test]
+	fn multi_proof_all_subsets() {
+		for n in 1..=9u32 {
+			let blobs = (0..n)
+				.map(|i| H256::repeat_byte(i as u8))
+				.collect::<Vec<_>>();
+			let messages = (0..n).map(|i| vec![i as u8; 40]).collect::<Vec<_>>();
+			let roots = roots(
+				merkle_root::<Keccak256, _>(blobs.iter()),
+				merkle_root::<Keccak256, _>(messages.iter()),
+			);
+
+			for subset in 1..(1u32 << n) {
+				let indices = (0..n)
+					.filter(|i| subset & (1 << i) != 0)
+					.collect::<Vec<_>>();
+
+				let proof =
+					MultiDataProof::new(SubTrie::DataSubmit, roots, &blobs, &indices).unwrap();
+				assert_eq!(proof.verify(SubTrie::DataSubmit), Ok(()));
+				let expected = indices
+					.iter()
+					.map(|&i| (i, blobs[i as usize]))
+					.collect::<Vec<_>>();
+				assert_eq!(proof.leaves, expected);
+				let single_len = indices.iter().map(|&i| proof_len(i, n)).sum::<usize>();
+				assert!(proof.proof.len() <= single_len);
+
+				let proof =
+					MultiDataProof::new(SubTrie::Bridge, roots, &messages, &indices).unwrap();
+				assert_eq!(proof.verify(SubTrie::Bridge), Ok(()));
+				assert_eq!(MultiDataProof::decode(&mut &proof.encode()[..]), Ok(proof));
+			}
+		}
+	}
+
+	#[test]
+	fn multi_proof_rejects_invalid_proofs() {
+		let blobs = (0..7u8).map(H256::repeat_byte).collect::<Vec<_>>();
+		let roots = roots(merkle_root::<Keccak256, _>(blobs.iter()), H256::zero());
+		let proof = MultiDataProof::new(SubTrie::DataSubmit, roots, &blobs, &[5, 1, 2, 5]).unwrap();
+		assert_eq!(proof.leaves.len(), 3);
+		assert_eq!(proof.verify(SubTrie::DataSubmit), Ok(()));
+
+		let invalid = |f: &dyn Fn(&mut MultiDataProof)| {
+			let mut invalid = proof.clone();
+			f(&mut invalid);
+			invalid.verify(SubTrie::DataSubmit).unwrap_err()
+		};
+		assert_eq!(invalid(&|p| p.leaves.clear()), ProofError::NoLeaves);
+		assert_eq!(
+			invalid(&|p| p.leaves.swap(0, 1)),
+			ProofError::UnsortedLeaves
+		);
+		assert_eq!(
+			invalid(&|p| p.leaves[1] = p.leaves[0]),
+			ProofError::UnsortedLeaves
+		);
+		assert_eq!(
+			invalid(&|p| p.number_of_leaves = 5),
+			ProofError::LeafIndexOutOfBounds {
+				leaf_index: 5,
+				number_of_leaves: 5
+			}
+		);
+		assert_eq!(
+			invalid(&|p| {
+				p.proof.pop();
+			}),
+			ProofError::InvalidProofLength {
+				expected: 4,
+				actual: 3
+			}
+		);
+		assert_eq!(
+			invalid(&|p| p.leaves[0].1 = blobs[0]),
+			ProofError::InvalidSubRoot
+		);
+		assert_eq!(
+			invalid(&|p| p.proof[0] = H256::zero()),
+			ProofError::InvalidSubRoot
+		);
+		assert_eq!(
+			invalid(&|p| p.roots.data_root = H256::zero()),
+			ProofError::InvalidDataRoot
+		);
+		assert_eq!(
+			proof.verify(SubTrie::Bridge),
+			Err(ProofError::InvalidSubRoot)
+		);
+
+		assert_eq!(
+			MultiDataProof::new(SubTrie::DataSubmit, roots, &blobs, &[]),
+			Err(ProofError::NoLeaves)
+		);
+		assert_eq!(
+			MultiDataProof::new(SubTrie::DataSubmit, roots, &blobs, &[7]),
+			Err(ProofError::LeafIndexOutOfBounds {
+				leaf_index: 7,
+				number_of_leaves: 7
+			})
+		);
+		assert_eq!(
+			MultiDataProof::new(SubTrie::DataSubmit, roots, &[[0u8; 20]], &[0]),
+			Err(ProofError::InvalidLeafLength(20))
+		);
+	}
+
+	#[test]
+	#[cfg(target_pointer_width = "64")]
+	fn multi_data_proof_rejects_too_many_leaves() {
+		let len = u32::MAX as usize + 1;
+		// Zero-sized leaves, so the slice does not need any memory.
+		let leaves: &[[u8; 0]] =
+			unsafe { core::slice::from_raw_parts(core::ptr::NonNull::dangling().as_ptr(), len) };
+		assert_eq!(
+			MultiDataProof::new(SubTrie::Bridge, TxDataRoots::default(), leaves, &[0]),
+			Err(ProofError::TooManyLeaves(len))
+		);
+	}
+
+	#[test]
+	fn tx_uid_conversions() {
+		let uid = TxUid::new(7, 3);
+		assert_eq!(uid, TxUid(tx_uid(7, 3)));
+		assert_eq!(tx_uid_deconstruct(uid.0), (7, 3));
+		assert_eq!(<(u32, u32)>::from(uid), (7, 3));
+		assert_eq!(TxUid::from((7, 3)), uid);
+		assert_eq!((uid.block(), uid.tx_index()), (7, 3));
+		assert_eq!(u64::from(uid), (7 << 32) | 3);
+
+		assert_eq!(uid.to_string(), "7:3");
+		assert_eq!("7:3".parse(), Ok(uid));
+		assert_eq!(TxUid::try_from("4294967295:0"), Ok(TxUid::new(u32::MAX, 0)));
+		for invalid in ["7", "7:", ":3", "7:3:1", "-1:3", "4294967296:0"] {
+			assert_eq!(TxUid::try_from(invalid), Err(TxUidParseError));
+		}
+
+		assert!(TxUid::new(1, u32::MAX) < TxUid::new(2, 0));
+		assert!(TxUid::new(2, 0) < TxUid::new(2, 1));
+	}
+
+	#[test]
+	fn tx_uid_encoding_is_a_compact_u64() {
+		let uid = TxUid::new(7, 3);
+		assert_eq!(uid.encode(), codec::Compact(uid.0).encode());
+
+		let data = BoundedData::try_from(vec![1, 2, 3]).unwrap();
+		let message = AddressedMessage::new(
+			Message::ArbitraryMessage(data),
+			H256::repeat_byte(1),
+			H256::repeat_byte(2),
+			1,
+			2,
+			uid,
+		);
+		let encoded = message.encode();
+		assert!(encoded.ends_with(&codec::Compact(uid.0).encode()));
+		assert_eq!(
+			AddressedMessage::decode(&mut encoded.as_slice()),
+			Ok(message)
+		);
+	}
+}
@@ -0,0 +1,169 @@
+use super::{merkle_layers, merkle_path, AddressedMessage, DataProof, ProofResponse, TxDataRoots};
+use crate::keccak256_concat;
+
+use primitive_types::H256;
+use sp_std::vec::Vec;
+
+/// Collects bridge messages, in the order they appear in the block, to build the bridge sub-trie.
+#[derive(Debug, Clone, Default)]
+pub struct BridgeTreeBuilder {
+	messages: Vec<AddressedMessage>,
+}
+
+impl BridgeTreeBuilder {
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Appends `message` as the next leaf.
+	pub fn push(&mut self, message: AddressedMessage) -> &mut Self {
+		self.messages.push(message);
+		self
+	}
+
+	pub fn build(self) -> BridgeTree {
+		let leaves = self
+			.messages
+			.iter()
+			.map(|message| keccak256_concat!(message.abi_encode()))
+			.collect();
+		BridgeTree {
+			messages: self.messages,
+			layers: merkle_layers(leaves),
+		}
+	}
+}
+
+impl Extend<AddressedMessage> for BridgeTreeBuilder {
+	fn extend<I: IntoIterator<Item = AddressedMessage>>(&mut self, iter: I) {
+		self.messages.extend(iter)
+	}
+}
+
+impl FromIterator<AddressedMessage> for BridgeTreeBuilder {
+	fn from_iter<I: IntoIterator<Item = AddressedMessage>>(iter: I) -> Self {
+		Self {
+			messages: iter.into_iter().collect(),
+		}
+	}
+}
+
+/// Keccak 256 binary Merkle tree over ABI encoded bridge messages.
+/// Its root is the same one `binary-merkle-tree` computes over the encoded messages.
+#[derive(Debug, Clone)]
+pub struct BridgeTree {
+	messages: Vec<AddressedMessage>,
+	layers: Vec<Vec<H256>>,
+}
+
+impl BridgeTree {
+	pub fn messages(&self) -> &[AddressedMessage] {
+		&self.messages
+	}
+
+	/// Leaf hashes, as Keccak 256 of the ABI encoded messages.
+	pub fn leaves(&self) -> &[H256] {
+		self.layers.first().map(Vec::as_slice).unwrap_or_default()
+	}
+
+	/// Bridge root, or zero if there are no messages.
+	pub fn root(&self) -> H256 {
+		self.layers
+			.last()
+			.and_then(|root| root.first())
+			.copied()
+			.unwrap_or_default()
+	}
+
+	/// Roots of the block data, given the root of the submitted data.
+	pub fn tx_data_roots(&self, blob_root: H256) -> TxDataRoots {
+		let bridge_root = self.root();
+		TxDataRoots {
+			data_root: keccak256_concat!(blob_root, bridge_root),
+			blob_root,
+			bridge_root,
+		}
+	}
+
+	/// Proof of the `index`-th message, or `None` if there is no such message.
+	pub fn proof_response(&self, blob_root: H256, index: u32) -> Option<ProofResponse> {
+		let message = self.messages.get(index as usize)?.clone();
+		let leaf = *self.leaves().get(index as usize)?;
+
+		let data_proof = DataProof {
+			roots: self.tx_data_roots(blob_root),
+			proof: merkle_path(&self.layers, index),
+			number_of_leaves: u32::try_from(self.messages.len()).ok()?,
+			leaf_index: index,
+			leaf,
+		};
+		Some(ProofResponse::new(data_proof, Some(message)))
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::{
+		data_proof::{BoundedData, Message, SubTrie, TxUid},
+		Keccak256,
+	};
+	use binary_merkle_tree::merkle_root;
+
+	fn message(id: u64) -> AddressedMessage {
+		let data = BoundedData::try_from(vec![id as u8; 10]).unwrap();
+		AddressedMessage::new(
+			Message::ArbitraryMessage(data),
+			H256::repeat_byte(1),
+			H256::repeat_byte(2),
+			1,
+			2,
+			TxUid(id),
+		)
+	}
+
+	#[test]
+	fn root_matches_binary_merkle_tree() {
+		let blob_root = H256::repeat_byte(0xbb);
+		for n in 0..=9 {
+			let tree = (0..n).map(message).collect::<BridgeTreeBuilder>().build();
+			let encoded = tree
+				.messages()
+				.iter()
+				.map(AddressedMessage::abi_encode)
+				.collect::<Vec<_>>();
+			assert_eq!(tree.root(), merkle_root::<Keccak256, _>(encoded.iter()));
+			assert_eq!(tree.leaves().len(), n as usize);
+
+			let roots = tree.tx_data_roots(blob_root);
+			assert!(roots.is_valid());
+			for index in 0..n as u32 {
+				let response = tree.proof_response(blob_root, index).unwrap();
+				assert_eq!(response.data_proof.roots, roots);
+				assert_eq!(
+					response.message.as_ref(),
+					Some(&tree.messages()[index as usize])
+				);
+				assert_eq!(
+					response
+						.data_proof
+						.verify(SubTrie::Bridge, &encoded[index as usize]),
+					Ok(())
+				);
+			}
+			assert!(tree.proof_response(blob_root, n as u32).is_none());
+		}
+	}
+
+	#[test]
+	fn builder_keeps_message_order() {
+		let mut builder = BridgeTreeBuilder::new();
+		builder.push(message(1)).push(message(0));
+		builder.extend([message(2)]);
+		let tree = builder.build();
+
+		let ids = tree.messages().iter().map(|m| m.id.0).collect::<Vec<_>>();
+		assert_eq!(ids, vec![1, 0, 2]);
+		assert_eq!(tree.leaves()[0], keccak256_concat!(message(1).abi_encode()));
+	}
+}
@@ -1,12 +1,15 @@
-use super::BoundedData;
+use super::{BoundedData, TxUid};
 
+use bounded_collections::{BoundedVec, ConstU32};
 use codec::{Decode, Encode};
 use derive_more::{Constructor, From};
-use primitive_types::H256;
+use primitive_types::{H256, U256};
 use scale_info::TypeInfo;
-use sp_std::{vec, vec::Vec};
+use sp_std::{boxed::Box, vec, vec::Vec};
+use thiserror_no_std::Error;
 
-use ethabi_decode::{encode, Token, U256};
+use crate::ensure;
+use ethabi_decode::{decode, encode, ParamType, Token, U256 as AbiU256};
 
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
@@ -17,6 +20,84 @@ use serde::{Deserialize, Serialize};
 pub enum MessageType {
 	ArbitraryMessage,
 	FungibleToken,
+	NonFungibleToken,
+	Batch,
+	FungibleTokenU256,
+}
+
+/// Max number of messages in a batch
+pub const BATCH_MAX_LENGTH: u32 = 32;
+
+/// Messages bridged together
+pub type BoundedBatch = BoundedVec<BatchItem, ConstU32<BATCH_MAX_LENGTH>>;
+
+/// Message of a [`Message::Batch`].
+///
+/// It has every variant of [`Message`] but `Batch`, under the same SCALE index, so batches cannot
+/// be nested and are encoded as a sequence of messages.
+#[derive(Debug, Clone, Encode, Decode, PartialEq, Eq, From, TypeInfo)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
+pub enum BatchItem {
+	#[codec(index = 0)]
+	ArbitraryMessage(BoundedData),
+	#[codec(index = 1)]
+	FungibleToken {
+		asset_id: H256,
+		#[codec(compact)]
+		amount: u128,
+	},
+	#[codec(index = 2)]
+	#[from(ignore)]
+	NonFungibleToken { collection: H256, token_id: U256 },
+	#[codec(index = 4)]
+	#[from(ignore)]
+	FungibleTokenU256 { asset_id: H256, amount: U256 },
+}
+
+impl From<BatchItem> for Message {
+	fn from(item: BatchItem) -> Self {
+		match item {
+			BatchItem::ArbitraryMessage(data) => Message::ArbitraryMessage(data),
+			BatchItem::FungibleToken { asset_id, amount } => {
+				Message::FungibleToken { asset_id, amount }
+			},
+			BatchItem::NonFungibleToken {
+				collection,
+				token_id,
+			} => Message::NonFungibleToken {
+				collection,
+				token_id,
+			},
+			BatchItem::FungibleTokenU256 { asset_id, amount } => {
+				Message::FungibleTokenU256 { asset_id, amount }
+			},
+		}
+	}
+}
+
+impl TryFrom<Message> for BatchItem {
+	type Error = MessageDecodeError;
+
+	fn try_from(message: Message) -> Result<Self, Self::Error> {
+		match message {
+			Message::ArbitraryMessage(data) => Ok(BatchItem::ArbitraryMessage(data)),
+			Message::FungibleToken { asset_id, amount } => {
+				Ok(BatchItem::FungibleToken { asset_id, amount })
+			},
+			Message::NonFungibleToken {
+				collection,
+				token_id,
+			} => Ok(BatchItem::NonFungibleToken {
+				collection,
+				token_id,
+			}),
+			Message::FungibleTokenU256 { asset_id, amount } => {
+				Ok(BatchItem::FungibleTokenU256 { asset_id, amount })
+			},
+			Message::Batch(..) => Err(MessageDecodeError::NestedBatch),
+		}
+	}
 }
 
 /// Possible types of Messages allowed by Avail to bridge to other chains.
@@ -30,6 +111,19 @@ pub enum Message {
 		#[codec(compact)]
 		amount: u128,
 	},
+	#[from(ignore)]
+	NonFungibleToken {
+		collection: H256,
+		token_id: U256,
+	},
+	/// Messages bridged together. Batches cannot be nested, see [`BatchItem`].
+	Batch(BoundedBatch),
+	/// Fungible token whose amount does not fit in `u128`.
+	#[from(ignore)]
+	FungibleTokenU256 {
+		asset_id: H256,
+		amount: U256,
+	},
 }
 
 impl Message {
@@ -37,6 +131,9 @@ impl Message {
 		match self {
 			Message::ArbitraryMessage(..) => vec![0x01],
 			Message::FungibleToken { .. } => vec![0x02],
+			Message::NonFungibleToken { .. } => vec![0x03],
+			Message::Batch(..) => vec![0x04],
+			Message::FungibleTokenU256 { .. } => vec![0x05],
 		}
 	}
 
@@ -44,15 +141,176 @@ impl Message {
 		match self {
 			Message::ArbitraryMessage(..) => MessageType::ArbitraryMessage,
 			Message::FungibleToken { .. } => MessageType::FungibleToken,
+			Message::NonFungibleToken { .. } => MessageType::NonFungibleToken,
+			Message::Batch(..) => MessageType::Batch,
+			Message::FungibleTokenU256 { .. } => MessageType::FungibleTokenU256,
 		}
 	}
 
 	pub fn is_empty(&self) -> bool {
 		match self {
 			Message::ArbitraryMessage(data) => data.is_empty(),
-			Message::FungibleToken { .. } => false,
+			Message::Batch(messages) => messages.is_empty(),
+			Message::FungibleToken { .. }
+			| Message::NonFungibleToken { .. }
+			| Message::FungibleTokenU256 { .. } => false,
 		}
 	}
+
+	/// ABI encoding of the message content, without its selector.
+	///
+	/// Each message of a batch is encoded as its selector followed by its content.
+	fn abi_data(&self) -> Vec<u8> {
+		match self {
+			Message::ArbitraryMessage(data) => data.clone().into_inner(),
+			Message::FungibleToken { asset_id, amount } => {
+				id_and_uint_data(asset_id, AbiU256::from(*amount))
+			},
+			Message::NonFungibleToken {
+				collection: id,
+				token_id: value,
+			}
+			| Message::FungibleTokenU256 {
+				asset_id: id,
+				amount: value,
+			} => id_and_uint_data(id, to_abi_uint(value)),
+			Message::Batch(items) => {
+				let items = items
+					.iter()
+					.map(|item| {
+						let message = Message::from(item.clone());
+						Token::Bytes([message.selector_abi_encode(), message.abi_data()].concat())
+					})
+					.collect();
+				encode(&[Token::Array(items)])
+			},
+		}
+	}
+}
+
+/// Encodes the content of token messages, as a 32 bytes identifier followed by an integer.
+fn id_and_uint_data(id: &H256, value: AbiU256) -> Vec<u8> {
+	encode(&[Token::FixedBytes(id.encode()), Token::Uint(value)])
+}
+
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum MessageDecodeError {
+	#[error("Message is not a valid ABI encoding")]
+	InvalidEncoding,
+	#[error("Unknown message selector {0:#04x}")]
+	InvalidSelector(u8),
+	#[error("Message data has {0} bytes, exceeding the bridge limit")]
+	DataTooLong(usize),
+	#[error("Integer value is out of range")]
+	ValueOutOfRange,
+	#[error("Message is not canonically encoded")]
+	NonCanonical,
+	#[error("Batch has {0} messages, exceeding the bridge limit")]
+	BatchTooLong(usize),
+	#[error("Batches cannot be nested")]
+	NestedBatch,
+}
+
+impl Message {
+	/// Decodes the message from its selector and the data produced by
+	/// [`AddressedMessage::abi_encode`].
+	fn abi_decode(selector: u8, data: Vec<u8>) -> Result<Self, MessageDecodeError> {
+		match selector {
+			0x01 => {
+				let len = data.len();
+				let data = BoundedData::try_from(data)
+					.map_err(|_| MessageDecodeError::DataTooLong(len))?;
+				Ok(Message::ArbitraryMessage(data))
+			},
+			0x02 => {
+				let (asset_id, amount) = id_and_uint(&data)?;
+				ensure!(amount.bits() <= 128, MessageDecodeError::ValueOutOfRange);
+				Ok(Message::FungibleToken {
+					asset_id,
+					amount: amount.as_u128(),
+				})
+			},
+			0x03 => {
+				let (collection, token_id) = id_and_uint(&data)?;
+				Ok(Message::NonFungibleToken {
+					collection,
+					token_id: from_abi_uint(token_id),
+				})
+			},
+			0x04 => {
+				let items = match decode(&[ParamType::Array(Box::new(ParamType::Bytes))], &data)
+					.map_err(|_| MessageDecodeError::InvalidEncoding)?
+					.pop()
+				{
+					Some(Token::Array(items)) => items,
+					_ => return Err(MessageDecodeError::InvalidEncoding),
+				};
+				let len = items.len();
+				let items = items
+					.into_iter()
+					.map(|item| match item {
+						Token::Bytes(item) => match item.split_first() {
+							Some((0x04, _)) => Err(MessageDecodeError::NestedBatch),
+							Some((selector, data)) => {
+								BatchItem::try_from(Self::abi_decode(*selector, data.to_vec())?)
+							},
+							None => Err(MessageDecodeError::InvalidEncoding),
+						},
+						_ => Err(MessageDecodeError::InvalidEncoding),
+					})
+					.collect::<Result<Vec<_>, _>>()?;
+				let items = BoundedBatch::try_from(items)
+					.map_err(|_| MessageDecodeError::BatchTooLong(len))?;
+				Ok(Message::Batch(items))
+			},
+			0x05 => {
+				let (asset_id, amount) = id_and_uint(&data)?;
+				Ok(Message::FungibleTokenU256 {
+					asset_id,
+					amount: from_abi_uint(amount),
+				})
+			},
+			_ => Err(MessageDecodeError::InvalidSelector(selector)),
+		}
+	}
+}
+
+/// Decodes the content of token messages, as a 32 bytes identifier followed by an integer.
+fn id_and_uint(data: &[u8]) -> Result<(H256, AbiU256), MessageDecodeError> {
+	let mut tokens = decode(&[ParamType::FixedBytes(32), ParamType::Uint(256)], data)
+		.map_err(|_| MessageDecodeError::InvalidEncoding)?
+		.into_iter();
+	let id = H256::from_slice(&fixed_bytes(tokens.next(), 32)?);
+	let value = uint(tokens.next(), 256)?;
+	Ok((id, value))
+}
+
+fn to_abi_uint(value: &U256) -> AbiU256 {
+	let mut bytes = [0u8; 32];
+	value.to_big_endian(&mut bytes);
+	AbiU256::from_big_endian(&bytes)
+}
+
+fn from_abi_uint(value: AbiU256) -> U256 {
+	let mut bytes = [0u8; 32];
+	value.to_big_endian(&mut bytes);
+	U256::from_big_endian(&bytes)
+}
+
+fn fixed_bytes(token: Option<Token>, len: usize) -> Result<Vec<u8>, MessageDecodeError> {
+	match token {
+		Some(Token::FixedBytes(bytes)) if bytes.len() == len => Ok(bytes),
+		_ => Err(MessageDecodeError::InvalidEncoding),
+	}
+}
+
+/// Extracts an unsigned integer, checking that it fits in `bits` bits.
+fn uint(token: Option<Token>, bits: usize) -> Result<AbiU256, MessageDecodeError> {
+	match token {
+		Some(Token::Uint(value)) if value.bits() <= bits => Ok(value),
+		Some(Token::Uint(_)) => Err(MessageDecodeError::ValueOutOfRange),
+		_ => Err(MessageDecodeError::InvalidEncoding),
+	}
 }
 
 /// Message type used to bridge between Avail & other chains
@@ -67,35 +325,313 @@ pub struct AddressedMessage {
 	pub origin_domain: u32,
 	#[codec(compact)]
 	pub destination_domain: u32,
-	/// Unique identifier for the message
-	#[codec(compact)]
-	pub id: u64,
+	/// Unique identifier for the message, encoded as a compact `u64`
+	pub id: TxUid,
 }
 
 impl AddressedMessage {
-	fn abi_data(&self) -> Vec<u8> {
-		match &self.message {
-			Message::ArbitraryMessage(data) => data.clone().into_inner(),
-			Message::FungibleToken { asset_id, amount } => {
-				let data = [
-					Token::FixedBytes(asset_id.encode()),
-					Token::Uint(U256::from(*amount)),
-				];
-				encode(&data)
-			},
-		}
-	}
-
 	pub fn abi_encode(&self) -> Vec<u8> {
-		let data = self.abi_data();
+		let data = self.message.abi_data();
 		encode(&[Token::Tuple(vec![
 			Token::FixedBytes(self.message.selector_abi_encode()),
 			Token::FixedBytes(self.from.to_fixed_bytes().to_vec()),
 			Token::FixedBytes(self.to.to_fixed_bytes().to_vec()),
-			Token::Uint(U256::from(self.origin_domain)),
-			Token::Uint(U256::from(self.destination_domain)),
+			Token::Uint(AbiU256::from(self.origin_domain)),
+			Token::Uint(AbiU256::from(self.destination_domain)),
 			Token::Bytes(data),
-			Token::Uint(U256::from(self.id)),
+			Token::Uint(AbiU256::from(u64::from(self.id))),
 		])])
 	}
+
+	/// Decodes the message encoded by [`Self::abi_encode`].
+	///
+	/// Only the canonical encoding is accepted, so `abi_encode` returns exactly the decoded bytes.
+	pub fn abi_decode(encoded: &[u8]) -> Result<Self, MessageDecodeError> {
+		// The message is encoded as a dynamic tuple, so its fields follow the offset of the tuple.
+		let fields = encoded
+			.get(32..)
+			.ok_or(MessageDecodeError::InvalidEncoding)?;
+		let types = [
+			ParamType::FixedBytes(1),
+			ParamType::FixedBytes(32),
+			ParamType::FixedBytes(32),
+			ParamType::Uint(32),
+			ParamType::Uint(32),
+			ParamType::Bytes,
+			ParamType::Uint(64),
+		];
+		let mut tokens = decode(&types, fields)
+			.map_err(|_| MessageDecodeError::InvalidEncoding)?
+			.into_iter();
+
+		let selector = fixed_bytes(tokens.next(), 1)?[0];
+		let from = H256::from_slice(&fixed_bytes(tokens.next(), 32)?);
+		let to = H256::from_slice(&fixed_bytes(tokens.next(), 32)?);
+		let origin_domain = uint(tokens.next(), 32)?.low_u32();
+		let destination_domain = uint(tokens.next(), 32)?.low_u32();
+		let data = match tokens.next() {
+			Some(Token::Bytes(data)) => data,
+			_ => return Err(MessageDecodeError::InvalidEncoding),
+		};
+		let id = TxUid(uint(tokens.next(), 64)?.low_u64());
+
+		let message = Self {
+			message: Message::abi_decode(selector, data)?,
+			from,
+			to,
+			origin_domain,
+			destination_domain,
+			id,
+		};
+		if message.abi_encode() != encoded {
+			return Err(MessageDecodeError::NonCanonical);
+		}
+		Ok(message)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::data_proof::BOUNDED_DATA_MAX_LENGTH;
+	use proptest::{collection::vec, prelude::*};
+
+	fn single_message_strategy() -> impl Strategy<Value = Message> {
+		prop_oneof![
+			vec(any::<u8>(), 0..2048)
+				.prop_map(|data| Message::ArbitraryMessage(BoundedData::try_from(data).unwrap())),
+			(any::<[u8; 32]>(), any::<u128>()).prop_map(|(asset_id, amount)| {
+				Message::FungibleToken {
+					asset_id: H256(asset_id),
+					amount,
+				}
+			}),
+			(any::<[u8; 32]>(), any::<[u8; 32]>()).prop_map(|(collection, token_id)| {
+				Message::NonFungibleToken {
+					collection: H256(collection),
+					token_id: U256::from_big_endian(&token_id),
+				}
+			}),
+			(any::<[u8; 32]>(), any::<[u8; 32]>()).prop_map(|(asset_id, amount)| {
+				Message::FungibleTokenU256 {
+					asset_id: H256(asset_id),
+					amount: U256::from_big_endian(&amount),
+				}
+			}),
+		]
+	}
+
+	fn message_strategy() -> impl Strategy<Value = Message> {
+		prop_oneof![
+			single_message_strategy(),
+			vec(single_message_strategy(), 0..=BATCH_MAX_LENGTH as usize).prop_map(|messages| {
+				let items = messages
+					.into_iter()
+					.map(|message| BatchItem::try_from(message).unwrap())
+					.collect::<Vec<_>>();
+				Message::Batch(BoundedBatch::try_from(items).unwrap())
+			}),
+		]
+	}
+
+	fn addressed_message_strategy() -> impl Strategy<Value = AddressedMessage> {
+		(
+			message_strategy(),
+			any::<[u8; 32]>(),
+			any::<[u8; 32]>(),
+			any::<u32>(),
+			any::<u32>(),
+			any::<u64>(),
+		)
+			.prop_map(|(message, from, to, origin, destination, id)| {
+				AddressedMessage::new(
+					message,
+					H256(from),
+					H256(to),
+					origin,
+					destination,
+					TxUid(id),
+				)
+			})
+	}
+
+	proptest! {
+	#[test]
+	fn abi_decode_round_trips(message in addressed_message_strategy()) {
+		let encoded = message.abi_encode();
+		prop_assert_eq!(AddressedMessage::abi_decode(&encoded), Ok(message));
+	}
+
+	#[test]
+	fn scale_round_trips(message in addressed_message_strategy()) {
+		let encoded = message.encode();
+		prop_assert_eq!(AddressedMessage::decode(&mut encoded.as_slice()), Ok(message));
+	}
+
+	#[test]
+	fn abi_decode_rejects_trailing_bytes(message in addressed_message_strategy(), extra in vec(any::<u8>(), 1..64)) {
+		let mut encoded = message.abi_encode();
+		encoded.extend(extra);
+		prop_assert!(AddressedMessage::abi_decode(&encoded).is_err());
+	}
+
+	#[test]
+	fn abi_decode_rejects_truncated(message in addressed_message_strategy(), cut in 1usize..64) {
+		let encoded = message.abi_encode();
+		let len = encoded.len().saturating_sub(cut);
+		prop_assert!(AddressedMessage::abi_decode(&encoded[..len]).is_err());
+	}
+	}
+
+	fn message(data: Vec<u8>) -> AddressedMessage {
+		let data = BoundedData::try_from(data).unwrap();
+		AddressedMessage::new(
+			data.into(),
+			H256::repeat_byte(1),
+			H256::repeat_byte(2),
+			1,
+			2,
+			TxUid(3),
+		)
+	}
+
+	#[test]
+	fn abi_decode_max_length() {
+		let max = message(vec![0xab; BOUNDED_DATA_MAX_LENGTH as usize]);
+		assert_eq!(AddressedMessage::abi_decode(&max.abi_encode()), Ok(max));
+
+		let encoded = encode(&[Token::Tuple(vec![
+			Token::FixedBytes(vec![0x01]),
+			Token::FixedBytes(vec![1; 32]),
+			Token::FixedBytes(vec![2; 32]),
+			Token::Uint(1.into()),
+			Token::Uint(2.into()),
+			Token::Bytes(vec![0xab; BOUNDED_DATA_MAX_LENGTH as usize + 1]),
+			Token::Uint(3.into()),
+		])]);
+		assert_eq!(
+			AddressedMessage::abi_decode(&encoded),
+			Err(MessageDecodeError::DataTooLong(
+				BOUNDED_DATA_MAX_LENGTH as usize + 1
+			))
+		);
+	}
+
+	#[test]
+	fn abi_decode_rejects_invalid_fields() {
+		let encoded = message(vec![1, 2, 3]).abi_encode();
+		let with = |offset: usize, byte: u8| {
+			let mut encoded = encoded.clone();
+			encoded[offset] = byte;
+			AddressedMessage::abi_decode(&encoded)
+		};
+
+		// Selector is the first byte of the first field, after the tuple offset
+		assert_eq!(
+			with(32, 0x06),
+			Err(MessageDecodeError::InvalidSelector(0x06))
+		);
+		assert_eq!(with(33, 0x01), Err(MessageDecodeError::NonCanonical));
+		// Most significant byte of the origin domain
+		assert_eq!(with(128, 0x01), Err(MessageDecodeError::ValueOutOfRange));
+		// Tuple offset
+		assert_eq!(with(31, 0x40), Err(MessageDecodeError::NonCanonical));
+		assert_eq!(
+			AddressedMessage::abi_decode(&[]),
+			Err(MessageDecodeError::InvalidEncoding)
+		);
+	}
+
+	#[test]
+	fn existing_encodings_are_unchanged() {
+		let data = BoundedData::try_from(vec![7u8; 3]).unwrap();
+		let arbitrary = Message::ArbitraryMessage(data);
+		assert_eq!(arbitrary.encode(), [0, 12, 7, 7, 7]);
+		assert_eq!(arbitrary.selector_abi_encode(), [0x01]);
+
+		let fungible = Message::FungibleToken {
+			asset_id: H256::repeat_byte(1),
+			amount: 5,
+		};
+		assert_eq!(fungible.encode(), [&[1u8][..], &[1; 32], &[20]].concat());
+		assert_eq!(fungible.selector_abi_encode(), [0x02]);
+		assert_eq!(MessageType::FungibleToken.encode(), [1]);
+	}
+
+	#[test]
+	fn abi_decode_rejects_invalid_batches() {
+		let item = Token::Bytes(
+			[
+				vec![0x02],
+				Message::FungibleToken {
+					asset_id: H256::zero(),
+					amount: 1,
+				}
+				.abi_data(),
+			]
+			.concat(),
+		);
+		let batch = |items: Vec<Token>| encode(&[Token::Array(items)]);
+
+		let valid = batch(vec![item.clone(); 2]);
+		assert!(matches!(
+			Message::abi_decode(0x04, valid),
+			Ok(Message::Batch(messages)) if messages.len() == 2
+		));
+		let too_long = batch(vec![item; BATCH_MAX_LENGTH as usize + 1]);
+		assert_eq!(
+			Message::abi_decode(0x04, too_long),
+			Err(MessageDecodeError::BatchTooLong(
+				BATCH_MAX_LENGTH as usize + 1
+			))
+		);
+		let nested = batch(vec![Token::Bytes([vec![0x04], batch(vec![])].concat())]);
+		assert_eq!(
+			Message::abi_decode(0x04, nested),
+			Err(MessageDecodeError::NestedBatch)
+		);
+		assert_eq!(
+			Message::abi_decode(0x04, batch(vec![Token::Bytes(vec![])])),
+			Err(MessageDecodeError::InvalidEncoding)
+		);
+	}
+
+	#[test]
+	fn scale_decode_rejects_nested_batches() {
+		let item = Message::FungibleToken {
+			asset_id: H256::zero(),
+			amount: 1,
+		};
+		let batch = Message::Batch(
+			BoundedBatch::try_from(vec![BatchItem::try_from(item.clone()).unwrap()]).unwrap(),
+		);
+		// Batch items are encoded as the messages they hold.
+		assert_eq!(batch.encode(), [&[3u8, 4][..], &item.encode()].concat());
+		assert_eq!(Message::decode(&mut batch.encode().as_slice()), Ok(batch));
+
+		// Batch of one empty batch.
+		let nested = [3u8, 4, 3, 0];
+		assert!(Message::decode(&mut nested.as_slice()).is_err());
+		// Deep nesting fails on the first nested batch, without recursion.
+		let deep = [3u8, 4].repeat(100_000);
+		assert!(Message::decode(&mut deep.as_slice()).is_err());
+		assert_eq!(
+			BatchItem::try_from(Message::Batch(BoundedBatch::default())),
+			Err(MessageDecodeError::NestedBatch)
+		);
+	}
+
+	#[test]
+	fn fungible_token_amount_is_bounded() {
+		let amount = U256::from(u128::MAX) + 1;
+		let large = Message::FungibleTokenU256 {
+			asset_id: H256::zero(),
+			amount,
+		};
+		assert_eq!(
+			Message::abi_decode(0x02, large.abi_data()),
+			Err(MessageDecodeError::ValueOutOfRange)
+		);
+		assert_eq!(Message::abi_decode(0x05, large.abi_data()), Ok(large));
+	}
 }
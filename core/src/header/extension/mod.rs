@@ -1,4 +1,4 @@
-use crate::{DataLookup, HeaderVersion};
+use crate::{DataLookup, ExtensionError, HeaderVersion};
 use codec::{Decode, Encode};
 use primitive_types::H256;
 use scale_info::TypeInfo;
@@ -9,6 +9,8 @@ use serde::{Deserialize, Serialize};
 use {sp_debug_derive::RuntimeDebug, sp_runtime_interface::pass_by::PassByCodec};
 
 pub mod v3;
+pub mod v4;
+pub mod v5;
 
 /// Header extension data.
 #[derive(PartialEq, Eq, Clone, Encode, Decode, TypeInfo)]
@@ -17,6 +19,8 @@ pub mod v3;
 #[repr(u8)]
 pub enum HeaderExtension {
 	V3(v3::HeaderExtension) = 2,
+	V4(v4::HeaderExtension) = 3,
+	V5(v5::HeaderExtension) = 4,
 }
 
 /// It forwards the call to the inner version of the header. Any invalid version will return the
@@ -25,12 +29,16 @@ macro_rules! forward_to_version {
 	($self:ident, $function:ident) => {{
 		match $self {
 			HeaderExtension::V3(ext) => ext.$function(),
+			HeaderExtension::V4(ext) => ext.$function(),
+			HeaderExtension::V5(ext) => ext.$function(),
 		}
 	}};
 
 	($self:ident, $function:ident, $arg:expr) => {{
 		match $self {
 			HeaderExtension::V3(ext) => ext.$function($arg),
+			HeaderExtension::V4(ext) => ext.$function($arg),
+			HeaderExtension::V5(ext) => ext.$function($arg),
 		}
 	}};
 }
@@ -52,21 +60,41 @@ impl HeaderExtension {
 		forward_to_version!(self, cols)
 	}
 
+	/// Checks that the commitment is consistent with the data lookup, including the empty and
+	/// faulty extensions.
+	pub fn validate(&self) -> Result<(), ExtensionError> {
+		forward_to_version!(self, validate)
+	}
+
 	pub fn get_empty_header(data_root: H256, version: HeaderVersion) -> HeaderExtension {
 		match version {
 			HeaderVersion::V3 => v3::HeaderExtension::get_empty_header(data_root).into(),
+			HeaderVersion::V4 => v4::HeaderExtension::get_empty_header(data_root).into(),
+			HeaderVersion::V5 => v5::HeaderExtension::get_empty_header(data_root).into(),
 		}
 	}
 
 	pub fn get_faulty_header(data_root: H256, version: HeaderVersion) -> HeaderExtension {
 		match version {
 			HeaderVersion::V3 => v3::HeaderExtension::get_faulty_header(data_root).into(),
+			HeaderVersion::V4 => v4::HeaderExtension::get_faulty_header(data_root).into(),
+			HeaderVersion::V5 => v5::HeaderExtension::get_faulty_header(data_root).into(),
 		}
 	}
 
 	pub fn get_header_version(&self) -> HeaderVersion {
 		match self {
 			HeaderExtension::V3(_) => HeaderVersion::V3,
+			HeaderExtension::V4(_) => HeaderVersion::V4,
+			HeaderExtension::V5(_) => HeaderVersion::V5,
+		}
+	}
+
+	/// Merkle root over the rows of each application, only present since `V5`.
+	pub fn app_rows_root(&self) -> Option<H256> {
+		match self {
+			HeaderExtension::V5(ext) => Some(ext.app_rows_root()),
+			_ => None,
 		}
 	}
 }
@@ -83,3 +111,34 @@ impl From<v3::HeaderExtension> for HeaderExtension {
 		Self::V3(ext)
 	}
 }
+
+impl From<v4::HeaderExtension> for HeaderExtension {
+	#[inline]
+	fn from(ext: v4::HeaderExtension) -> Self {
+		Self::V4(ext)
+	}
+}
+
+impl From<v5::HeaderExtension> for HeaderExtension {
+	#[inline]
+	fn from(ext: v5::HeaderExtension) -> Self {
+		Self::V5(ext)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use test_case::test_case;
+
+	#[test_case(HeaderVersion::V3; "v3")]
+	#[test_case(HeaderVersion::V4; "v4")]
+	#[test_case(HeaderVersion::V5; "v5")]
+	fn empty_and_faulty_are_valid(version: HeaderVersion) {
+		let data_root = H256::repeat_byte(1);
+		let empty = HeaderExtension::get_empty_header(data_root, version);
+		assert_eq!(empty.validate(), Ok(()));
+		let faulty = HeaderExtension::get_faulty_header(data_root, version);
+		assert_eq!(faulty.validate(), Ok(()));
+	}
+}
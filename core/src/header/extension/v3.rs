@@ -1,4 +1,4 @@
-use crate::{v3::KateCommitment, DataLookup};
+use crate::{v3::KateCommitment, DataLookup, ExtensionError};
 use codec::{Decode, Encode};
 use primitive_types::H256;
 use scale_info::TypeInfo;
@@ -35,6 +35,11 @@ impl HeaderExtension {
 		self.commitment.cols
 	}
 
+	/// Checks that the commitment is consistent with the data lookup.
+	pub fn validate(&self) -> Result<(), ExtensionError> {
+		self.commitment.validate(&self.app_lookup)
+	}
+
 	pub fn get_empty_header(data_root: H256) -> Self {
 		let empty_commitment: Vec<u8> = vec![];
 		let empty_app_lookup = DataLookup::new_empty();
@@ -0,0 +1,70 @@
+use crate::{v4::KateCommitment, DataLookup, ExtensionError};
+use codec::{Decode, Encode};
+use primitive_types::H256;
+use scale_info::TypeInfo;
+use sp_std::{vec, vec::Vec};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "runtime")]
+use sp_debug_derive::RuntimeDebug;
+
+#[derive(PartialEq, Eq, Clone, Encode, Decode, Default, TypeInfo)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
+#[cfg_attr(feature = "runtime", derive(RuntimeDebug))]
+pub struct HeaderExtension {
+	pub app_lookup: DataLookup,
+	pub commitment: KateCommitment,
+}
+
+impl HeaderExtension {
+	pub fn data_root(&self) -> H256 {
+		self.commitment.data_root
+	}
+
+	pub fn app_lookup(&self) -> &DataLookup {
+		&self.app_lookup
+	}
+
+	pub fn rows(&self) -> u16 {
+		self.commitment.rows
+	}
+
+	pub fn cols(&self) -> u16 {
+		self.commitment.cols
+	}
+
+	pub fn row_extension(&self) -> u16 {
+		self.commitment.row_extension
+	}
+
+	pub fn col_extension(&self) -> u16 {
+		self.commitment.col_extension
+	}
+
+	/// Checks that the commitment is consistent with the data lookup.
+	pub fn validate(&self) -> Result<(), ExtensionError> {
+		self.commitment.validate(&self.app_lookup)
+	}
+
+	pub fn get_empty_header(data_root: H256) -> Self {
+		let empty_commitment: Vec<u8> = vec![];
+		let empty_app_lookup = DataLookup::new_empty();
+		let commitment = KateCommitment::new(0, 0, 0, 0, data_root, empty_commitment);
+		HeaderExtension {
+			app_lookup: empty_app_lookup,
+			commitment,
+		}
+	}
+
+	pub fn get_faulty_header(data_root: H256) -> Self {
+		let empty_commitment: Vec<u8> = vec![];
+		let error_app_lookup = DataLookup::new_error();
+		let commitment = KateCommitment::new(0, 0, 0, 0, data_root, empty_commitment);
+		HeaderExtension {
+			app_lookup: error_app_lookup,
+			commitment,
+		}
+	}
+}
@@ -0,0 +1,309 @@
+use crate::{
+	constants::kate::{COMMITMENT_SIZE, EXTENSION_FACTOR},
+	data_proof::{
+		ensure_leaf_index, merkle_layers, merkle_path, merkle_root, proof_len, ProofError,
+	},
+	ensure, keccak256_concat,
+	v3::KateCommitment,
+	AppId, DataLookup, ExtensionError,
+};
+use codec::{Decode, Encode};
+use primitive_types::H256;
+use scale_info::TypeInfo;
+use sp_std::{vec, vec::Vec};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "runtime")]
+use sp_debug_derive::RuntimeDebug;
+
+/// Header extension which also commits to the rows of each application, so light clients can
+/// fetch and verify the commitments of their application only.
+#[derive(PartialEq, Eq, Clone, Encode, Decode, Default, TypeInfo)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
+#[cfg_attr(feature = "runtime", derive(RuntimeDebug))]
+pub struct HeaderExtension {
+	pub app_lookup: DataLookup,
+	pub commitment: KateCommitment,
+	/// Merkle root over the [`AppRows`] of each application, in `app_lookup` order.
+	pub app_rows_root: H256,
+}
+
+impl HeaderExtension {
+	pub fn data_root(&self) -> H256 {
+		self.commitment.data_root
+	}
+
+	pub fn app_lookup(&self) -> &DataLookup {
+		&self.app_lookup
+	}
+
+	pub fn rows(&self) -> u16 {
+		self.commitment.rows
+	}
+
+	pub fn cols(&self) -> u16 {
+		self.commitment.cols
+	}
+
+	pub fn app_rows_root(&self) -> H256 {
+		self.app_rows_root
+	}
+
+	/// Checks that the commitment is consistent with the data lookup, and that `app_rows_root`
+	/// commits to the [`AppRows`] they define.
+	pub fn validate(&self) -> Result<(), ExtensionError> {
+		let app_rows = app_rows(&self.app_lookup, &self.commitment)?;
+		ensure!(
+			app_rows_root(&app_rows) == self.app_rows_root,
+			ExtensionError::InvalidAppRowsRoot
+		);
+		Ok(())
+	}
+
+	pub fn get_empty_header(data_root: H256) -> Self {
+		let empty_commitment: Vec<u8> = vec![];
+		let empty_app_lookup = DataLookup::new_empty();
+		let commitment = KateCommitment::new(0, 0, data_root, empty_commitment);
+		HeaderExtension {
+			app_lookup: empty_app_lookup,
+			commitment,
+			app_rows_root: H256::zero(),
+		}
+	}
+
+	pub fn get_faulty_header(data_root: H256) -> Self {
+		let empty_commitment: Vec<u8> = vec![];
+		let error_app_lookup = DataLookup::new_error();
+		let commitment = KateCommitment::new(0, 0, data_root, empty_commitment);
+		HeaderExtension {
+			app_lookup: error_app_lookup,
+			commitment,
+			app_rows_root: H256::zero(),
+		}
+	}
+}
+
+/// Data rows of an application, along with their commitments.
+/// They are the leaves of the tree whose root is [`HeaderExtension::app_rows_root`].
+#[derive(PartialEq, Eq, Clone, Encode, Decode, Default, TypeInfo, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
+pub struct AppRows {
+	pub app_id: AppId,
+	/// First row of the application in the original data matrix.
+	#[codec(compact)]
+	pub start_row: u32,
+	/// Row after the last one of the application in the original data matrix.
+	#[codec(compact)]
+	pub end_row: u32,
+	/// Commitments of the rows, concatenated.
+	pub commitments: Vec<u8>,
+}
+
+impl AppRows {
+	pub fn leaf_hash(&self) -> H256 {
+		keccak256_concat!(self.encode())
+	}
+}
+
+/// Rows of each application in the original matrix, along with their commitments, as committed
+/// by [`HeaderExtension::app_rows_root`].
+///
+/// Applications without data have no rows. Fails if `commitment` is not consistent with `lookup`.
+pub fn app_rows(
+	lookup: &DataLookup,
+	commitment: &KateCommitment,
+) -> Result<Vec<AppRows>, ExtensionError> {
+	commitment.validate(lookup)?;
+
+	let cols = u32::from(commitment.cols);
+	// Data rows are the even rows of the extended matrix.
+	let row_commitment = |row: u32| {
+		let start = row
+			.checked_mul(EXTENSION_FACTOR)
+			.and_then(|row| usize::try_from(row).ok())?
+			.checked_mul(COMMITMENT_SIZE)?;
+		commitment
+			.commitment
+			.get(start..start.checked_add(COMMITMENT_SIZE)?)
+	};
+
+	lookup
+		.iter()
+		.filter(|(_, range)| !range.is_empty())
+		.map(|(app_id, range)| {
+			let start_row = range.start.checked_div(cols)?;
+			let end_row = range
+				.end
+				.checked_sub(1)?
+				.checked_div(cols)?
+				.checked_add(1)?;
+			let commitments = (start_row..end_row)
+				.map(row_commitment)
+				.collect::<Option<Vec<_>>>()?
+				.concat();
+
+			Some(AppRows {
+				app_id,
+				start_row,
+				end_row,
+				commitments,
+			})
+		})
+		.collect::<Option<Vec<_>>>()
+		// Unreachable, as `validate` checks that the lookup fits into the committed matrix.
+		.ok_or(ExtensionError::LookupTooLarge {
+			lookup_len: lookup.len(),
+			rows: commitment.rows,
+			cols: commitment.cols,
+		})
+}
+
+/// Keccak 256 binary Merkle root of `app_rows`, or zero if there are no applications.
+pub fn app_rows_root(app_rows: &[AppRows]) -> H256 {
+	let leaves = app_rows.iter().map(AppRows::leaf_hash).collect();
+	merkle_layers(leaves)
+		.last()
+		.and_then(|root| root.first())
+		.copied()
+		.unwrap_or_default()
+}
+
+/// Inclusion proof of the [`AppRows`] of an application.
+#[derive(PartialEq, Eq, Clone, Encode, Decode, Default, TypeInfo, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
+pub struct AppRowsProof {
+	/// Inner node hashes necessary to reconstruct the root hash given the leaf hash.
+	pub proof: Vec<H256>,
+	/// Number of applications in the tree.
+	#[codec(compact)]
+	pub number_of_leaves: u32,
+	/// Index of the application in the tree (0-based).
+	#[codec(compact)]
+	pub leaf_index: u32,
+}
+
+impl AppRowsProof {
+	/// Creates the proof of the `leaf_index`-th application of `app_rows`.
+	/// Returns `None` if there is no such application.
+	pub fn new(app_rows: &[AppRows], leaf_index: u32) -> Option<Self> {
+		let number_of_leaves = u32::try_from(app_rows.len()).ok()?;
+		ensure_leaf_index(leaf_index, number_of_leaves).ok()?;
+
+		let leaves = app_rows.iter().map(AppRows::leaf_hash).collect();
+		Some(Self {
+			proof: merkle_path(&merkle_layers(leaves), leaf_index),
+			number_of_leaves,
+			leaf_index,
+		})
+	}
+
+	/// Verifies that `app_rows` are included in the tree of `app_rows_root`.
+	pub fn verify(&self, app_rows_root: H256, app_rows: &AppRows) -> Result<(), ProofError> {
+		ensure_leaf_index(self.leaf_index, self.number_of_leaves)?;
+		let expected = proof_len(self.leaf_index, self.number_of_leaves);
+		ensure!(
+			self.proof.len() == expected,
+			ProofError::InvalidProofLength {
+				expected,
+				actual: self.proof.len()
+			}
+		);
+
+		let root = merkle_root(
+			app_rows.leaf_hash(),
+			&self.proof,
+			self.leaf_index,
+			self.number_of_leaves,
+		);
+		ensure!(root == app_rows_root, ProofError::InvalidSubRoot);
+		Ok(())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn app_rows(n: u32) -> Vec<AppRows> {
+		(0..n)
+			.zip(1..)
+			.map(|(i, end_row)| AppRows {
+				app_id: AppId(i),
+				start_row: i,
+				end_row,
+				commitments: vec![i as u8; 48],
+			})
+			.collect()
+	}
+
+	#[test]
+	fn validate_checks_app_rows_root() {
+		// 2x4 matrix, whose extended rows are committed as `[row; 48]`.
+		let commitment = (0u8..4).flat_map(|row| [row; 48]).collect::<Vec<_>>();
+		let commitment = KateCommitment::new(2, 4, H256::zero(), commitment);
+		let app_lookup =
+			DataLookup::from_id_and_len_iter([(1u32, 3u32), (2, 4)].into_iter()).unwrap();
+
+		let app_rows = super::app_rows(&app_lookup, &commitment).unwrap();
+		assert_eq!(
+			app_rows,
+			vec![
+				AppRows {
+					app_id: AppId(1),
+					start_row: 0,
+					end_row: 1,
+					commitments: vec![0; 48],
+				},
+				AppRows {
+					app_id: AppId(2),
+					start_row: 0,
+					end_row: 2,
+					commitments: [[0; 48], [2; 48]].concat(),
+				},
+			]
+		);
+
+		let mut extension = HeaderExtension {
+			app_lookup,
+			commitment,
+			app_rows_root: app_rows_root(&app_rows),
+		};
+		assert_eq!(extension.validate(), Ok(()));
+		extension.app_rows_root = H256::zero();
+		assert_eq!(
+			extension.validate(),
+			Err(ExtensionError::InvalidAppRowsRoot)
+		);
+	}
+
+	#[test]
+	fn app_rows_proofs() {
+		assert_eq!(app_rows_root(&[]), H256::zero());
+		assert!(AppRowsProof::new(&[], 0).is_none());
+
+		for n in 1..=9 {
+			let app_rows = app_rows(n);
+			let root = app_rows_root(&app_rows);
+			for (index, rows) in (0..n).zip(&app_rows) {
+				let proof = AppRowsProof::new(&app_rows, index).unwrap();
+				assert_eq!(proof.verify(root, rows), Ok(()));
+				assert_eq!(
+					AppRowsProof::decode(&mut proof.encode().as_slice()),
+					Ok(proof.clone())
+				);
+
+				let mut tampered = rows.clone();
+				tampered.commitments[0] ^= 1;
+				assert_eq!(
+					proof.verify(root, &tampered),
+					Err(ProofError::InvalidSubRoot)
+				);
+			}
+			assert!(AppRowsProof::new(&app_rows, n).is_none());
+		}
+	}
+}
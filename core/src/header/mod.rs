@@ -44,6 +44,9 @@ const LOG_TARGET: &str = "header";
 pub mod extension;
 pub use extension::HeaderExtension;
 
+pub mod verifier;
+pub use verifier::{HeaderChainError, HeaderChainVerifier};
+
 /// Abstraction over a block header for a substrate chain.
 #[derive(PartialEq, Eq, Clone, TypeInfo, Encode, Decode)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
@@ -441,6 +444,12 @@ mod tests {
 			extension::HeaderExtension::V3(ref mut ext) => {
 				ext.commitment.commitment = b"invalid commitment v3".to_vec();
 			},
+			extension::HeaderExtension::V4(ref mut ext) => {
+				ext.commitment.commitment = b"invalid commitment v4".to_vec();
+			},
+			extension::HeaderExtension::V5(ref mut ext) => {
+				ext.commitment.commitment = b"invalid commitment v5".to_vec();
+			},
 		};
 
 		(header, hash)
@@ -453,6 +462,12 @@ mod tests {
 			extension::HeaderExtension::V3(ref mut ext) => {
 				ext.commitment.data_root = H256::repeat_byte(2u8);
 			},
+			extension::HeaderExtension::V4(ref mut ext) => {
+				ext.commitment.data_root = H256::repeat_byte(2u8);
+			},
+			extension::HeaderExtension::V5(ref mut ext) => {
+				ext.commitment.data_root = H256::repeat_byte(2u8);
+			},
 		};
 
 		(header, hash)
@@ -465,6 +480,12 @@ mod tests {
 			extension::HeaderExtension::V3(ref mut ext) => {
 				ext.commitment.cols += 2;
 			},
+			extension::HeaderExtension::V4(ref mut ext) => {
+				ext.commitment.cols += 2;
+			},
+			extension::HeaderExtension::V5(ref mut ext) => {
+				ext.commitment.cols += 2;
+			},
 		};
 
 		(header, hash)
@@ -477,6 +498,12 @@ mod tests {
 			extension::HeaderExtension::V3(ref mut ext) => {
 				ext.commitment.rows += 2;
 			},
+			extension::HeaderExtension::V4(ref mut ext) => {
+				ext.commitment.rows += 2;
+			},
+			extension::HeaderExtension::V5(ref mut ext) => {
+				ext.commitment.rows += 2;
+			},
 		};
 
 		(header, hash)
@@ -0,0 +1,212 @@
+//! Verification of batches of headers synced from untrusted peers.
+
+use super::Header;
+use crate::{ensure, ExtensionError};
+use scale_info::TypeInfo;
+use sp_runtime::{
+	traits::{BlockNumber, Hash as HashT},
+	SaturatedConversion,
+};
+use thiserror_no_std::Error;
+
+#[derive(Error, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HeaderChainError {
+	#[error("Parent hash of block {0} does not match the hash of the previous header")]
+	ParentHashMismatch(u64),
+	#[error("Block {actual} does not follow block {previous}")]
+	NonConsecutiveNumber { previous: u64, actual: u64 },
+	#[error("Extension of block {number} is not valid: {error}")]
+	InvalidExtension { number: u64, error: ExtensionError },
+}
+
+/// Checks that headers form a chain and that their extensions are well formed.
+///
+/// The verifier keeps the number and hash of the last verified header, so consecutive batches
+/// must extend each other. It is only advanced if the whole batch is valid.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HeaderChainVerifier<N, H>
+where
+	N: BlockNumber,
+	H: HashT,
+{
+	tip: Option<(N, H::Output)>,
+}
+
+impl<N, H> Default for HeaderChainVerifier<N, H>
+where
+	N: BlockNumber,
+	H: HashT,
+{
+	fn default() -> Self {
+		Self { tip: None }
+	}
+}
+
+impl<N, H> HeaderChainVerifier<N, H>
+where
+	N: BlockNumber,
+	H: HashT,
+	H::Output: TypeInfo,
+{
+	/// Creates a verifier which accepts any header as the first one.
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Creates a verifier whose first header must be the child of `trusted`,
+	/// e.g. the last finalized header.
+	pub fn with_trusted(trusted: &Header<N, H>) -> Self {
+		Self {
+			tip: Some((trusted.number, trusted.hash())),
+		}
+	}
+
+	/// Number and hash of the last verified header.
+	pub fn tip(&self) -> Option<&(N, H::Output)> {
+		self.tip.as_ref()
+	}
+
+	/// Verifies `headers`, sorted by number, and moves the tip to the last one.
+	pub fn verify(&mut self, headers: &[Header<N, H>]) -> Result<(), HeaderChainError> {
+		let mut tip = self.tip;
+		for header in headers {
+			if let Some((previous, hash)) = &tip {
+				ensure!(
+					previous.checked_add(&N::one()) == Some(header.number),
+					HeaderChainError::NonConsecutiveNumber {
+						previous: (*previous).saturated_into(),
+						actual: header.number.saturated_into(),
+					}
+				);
+				ensure!(
+					&header.parent_hash == hash,
+					HeaderChainError::ParentHashMismatch(header.number.saturated_into())
+				);
+			}
+			header
+				.extension
+				.validate()
+				.map_err(|error| HeaderChainError::InvalidExtension {
+					number: header.number.saturated_into(),
+					error,
+				})?;
+			tip = Some((header.number, header.hash()));
+		}
+
+		self.tip = tip;
+		Ok(())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::{
+		constants::kate::COMMITMENT_SIZE,
+		header::{extension::v3, HeaderExtension},
+		kate_commitment, AppId, DataLookup,
+	};
+	use primitive_types::H256;
+	use sp_runtime::traits::BlakeTwo256;
+
+	type THeader = Header<u32, BlakeTwo256>;
+	type Verifier = HeaderChainVerifier<u32, BlakeTwo256>;
+
+	fn v3_extension(
+		rows: u16,
+		cols: u16,
+		lookup_len: u32,
+		commitment_len: usize,
+	) -> HeaderExtension {
+		let app_lookup =
+			DataLookup::from_id_and_len_iter([(AppId(0), lookup_len)].into_iter()).unwrap();
+		let commitment = kate_commitment::v3::KateCommitment::new(
+			rows,
+			cols,
+			H256::zero(),
+			vec![0u8; commitment_len],
+		);
+		v3::HeaderExtension {
+			app_lookup,
+			commitment,
+		}
+		.into()
+	}
+
+	fn chain(len: u32) -> Vec<THeader> {
+		let mut parent_hash = H256::zero();
+		(0..len)
+			.map(|number| {
+				let header = THeader {
+					parent_hash,
+					number,
+					extension: v3_extension(2, 4, 5, 4 * COMMITMENT_SIZE),
+					..Default::default()
+				};
+				parent_hash = header.hash();
+				header
+			})
+			.collect()
+	}
+
+	#[test]
+	fn accepts_linked_batches() {
+		let headers = chain(6);
+		let mut verifier = Verifier::new();
+		assert_eq!(verifier.verify(&headers[..3]), Ok(()));
+		assert_eq!(verifier.verify(&[]), Ok(()));
+		assert_eq!(verifier.verify(&headers[3..]), Ok(()));
+		assert_eq!(verifier.tip(), Some(&(5, headers[5].hash())));
+
+		let mut verifier = Verifier::with_trusted(&headers[2]);
+		assert_eq!(verifier.verify(&headers[3..]), Ok(()));
+		assert_eq!(
+			Verifier::with_trusted(&headers[2]).verify(&headers[4..]),
+			Err(HeaderChainError::NonConsecutiveNumber {
+				previous: 2,
+				actual: 4
+			})
+		);
+	}
+
+	#[test]
+	fn rejects_broken_links() {
+		let mut headers = chain(4);
+		headers[2].parent_hash = H256::repeat_byte(1);
+		let mut verifier = Verifier::new();
+		assert_eq!(
+			verifier.verify(&headers),
+			Err(HeaderChainError::ParentHashMismatch(2))
+		);
+		assert_eq!(verifier.tip(), None);
+
+		let mut headers = chain(4);
+		headers.swap(1, 2);
+		assert_eq!(
+			Verifier::new().verify(&headers),
+			Err(HeaderChainError::NonConsecutiveNumber {
+				previous: 0,
+				actual: 2
+			})
+		);
+	}
+
+	#[test]
+	fn rejects_invalid_extensions() {
+		let mut headers = chain(3);
+		headers[1].extension = v3_extension(2, 4, 9, 4 * COMMITMENT_SIZE);
+		let mut verifier = Verifier::new();
+		assert_eq!(
+			verifier.verify(&headers),
+			Err(HeaderChainError::InvalidExtension {
+				number: 1,
+				error: ExtensionError::LookupTooLarge {
+					lookup_len: 9,
+					rows: 2,
+					cols: 4
+				}
+			})
+		);
+		assert_eq!(verifier.tip(), None);
+	}
+}
@@ -7,4 +7,6 @@ use sp_runtime_interface::pass_by::PassByCodec;
 #[cfg_attr(feature = "runtime", derive(PassByCodec))]
 pub enum HeaderVersion {
 	V3 = 2, // Current one
+	V4 = 3, // Two-dimensional extension
+	V5 = 4, // Commitments of application rows
 }
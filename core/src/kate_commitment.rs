@@ -1,12 +1,79 @@
-use crate::from_substrate::HexDisplay;
+use crate::{
+	constants::kate::{COMMITMENT_SIZE, EXTENSION_FACTOR},
+	ensure,
+	from_substrate::HexDisplay,
+	DataLookup,
+};
 use codec::{Decode, Encode};
 use primitive_types::H256;
 use scale_info::TypeInfo;
 use sp_std::{fmt, vec::Vec};
+use thiserror_no_std::Error;
 
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 
+/// Inconsistency between a Kate commitment and the data lookup of its header extension.
+#[derive(Error, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExtensionError {
+	#[error("Data lookup has {lookup_len} chunks, more than {rows}x{cols}")]
+	LookupTooLarge {
+		lookup_len: u32,
+		rows: u16,
+		cols: u16,
+	},
+	#[error("Commitment has {actual} bytes, expected {expected}")]
+	InvalidCommitmentLength { expected: usize, actual: usize },
+	#[error("Faulty extension must have neither dimensions nor commitment")]
+	NonEmptyFaulty,
+	#[error("Root of the application rows does not match the data lookup and commitment")]
+	InvalidAppRowsRoot,
+	#[error("Extension factors {row_extension}x{col_extension} are not valid for the matrix")]
+	InvalidExtensionFactors {
+		row_extension: u16,
+		col_extension: u16,
+	},
+}
+
+/// Checks that `commitment` holds exactly one commitment per committed row.
+fn validate_commitment_len(committed_rows: usize, commitment: &[u8]) -> Result<(), ExtensionError> {
+	let expected = committed_rows.saturating_mul(COMMITMENT_SIZE);
+	ensure!(
+		commitment.len() == expected,
+		ExtensionError::InvalidCommitmentLength {
+			expected,
+			actual: commitment.len()
+		}
+	);
+	Ok(())
+}
+
+/// Checks that `lookup` fits into a `rows`x`cols` matrix, and that faulty lookups come with
+/// neither dimensions nor commitment, as built by `get_faulty_header`.
+fn validate_lookup(
+	rows: u16,
+	cols: u16,
+	commitment: &[u8],
+	lookup: &DataLookup,
+) -> Result<(), ExtensionError> {
+	if lookup.is_error() {
+		ensure!(
+			rows == 0 && cols == 0 && commitment.is_empty(),
+			ExtensionError::NonEmptyFaulty
+		);
+	}
+	let lookup_len = lookup.len();
+	ensure!(
+		lookup_len <= u32::from(rows).saturating_mul(u32::from(cols)),
+		ExtensionError::LookupTooLarge {
+			lookup_len,
+			rows,
+			cols
+		}
+	);
+	Ok(())
+}
+
 pub mod v3 {
 	use super::*;
 
@@ -37,6 +104,22 @@ pub mod v3 {
 				data_root,
 			}
 		}
+
+		/// Number of row commitments, one per row of the extended matrix.
+		pub fn committed_rows(&self) -> usize {
+			usize::from(self.rows).saturating_mul(EXTENSION_FACTOR as usize)
+		}
+
+		/// Checks that `commitment` holds one commitment per row of the extended matrix.
+		pub fn validate_commitment(&self) -> Result<(), ExtensionError> {
+			validate_commitment_len(self.committed_rows(), &self.commitment)
+		}
+
+		/// Checks that the commitment is consistent with `lookup`, the data lookup of its header.
+		pub fn validate(&self, lookup: &DataLookup) -> Result<(), ExtensionError> {
+			validate_lookup(self.rows, self.cols, &self.commitment, lookup)?;
+			self.validate_commitment()
+		}
 	}
 
 	impl fmt::Debug for KateCommitment {
@@ -53,3 +136,175 @@ pub mod v3 {
 		}
 	}
 }
+
+pub mod v4 {
+	use super::*;
+
+	/// Commitment of a two-dimensional Reed-Solomon extended grid.
+	///
+	/// `commitment` holds the commitments of the original `rows` only. Columns are extended by
+	/// `row_extension` and rows by `col_extension`.
+	///
+	/// Light clients verify any cell of the extended grid from the header alone:
+	/// - commitments of the extended rows are the Reed-Solomon extension, by `row_extension`, of
+	///   the original commitments (see `kate_recovery::commitments::extend_v4`);
+	/// - extending a row by `col_extension` keeps the degree of its polynomial, so cells on
+	///   extended columns are proven against the commitment of their row.
+	#[derive(PartialEq, Eq, Clone, Default, Encode, Decode, TypeInfo)]
+	#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+	#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
+	#[cfg_attr(feature = "serde", serde(deny_unknown_fields))]
+	pub struct KateCommitment {
+		/// Rows
+		#[codec(compact)]
+		pub rows: u16,
+		/// Cols
+		#[codec(compact)]
+		pub cols: u16,
+		/// Extension factor of the columns.
+		#[codec(compact)]
+		pub row_extension: u16,
+		/// Extension factor of the rows.
+		#[codec(compact)]
+		pub col_extension: u16,
+		/// Plonk commitment.
+		pub commitment: Vec<u8>,
+		/// The merkle root of the data submitted
+		pub data_root: H256,
+	}
+
+	impl KateCommitment {
+		pub fn new(
+			rows: u16,
+			cols: u16,
+			row_extension: u16,
+			col_extension: u16,
+			data_root: H256,
+			commitment: Vec<u8>,
+		) -> Self {
+			Self {
+				rows,
+				cols,
+				row_extension,
+				col_extension,
+				commitment,
+				data_root,
+			}
+		}
+
+		/// Number of row commitments, one per row of the original matrix.
+		pub fn committed_rows(&self) -> usize {
+			usize::from(self.rows)
+		}
+
+		/// Checks that `commitment` holds one commitment per row of the original matrix.
+		pub fn validate_commitment(&self) -> Result<(), ExtensionError> {
+			validate_commitment_len(self.committed_rows(), &self.commitment)
+		}
+
+		/// Checks that extension factors are non-zero powers of two, and that extended columns
+		/// fit into `u16`. Empty and faulty commitments, without dimensions, have no extension.
+		pub fn validate_extension(&self) -> Result<(), ExtensionError> {
+			let is_factor = |factor: u16| factor.is_power_of_two();
+			let valid = if self.rows == 0 && self.cols == 0 {
+				self.row_extension == 0 && self.col_extension == 0
+			} else {
+				is_factor(self.row_extension)
+					&& is_factor(self.col_extension)
+					&& self.cols.checked_mul(self.col_extension).is_some()
+			};
+			ensure!(
+				valid,
+				ExtensionError::InvalidExtensionFactors {
+					row_extension: self.row_extension,
+					col_extension: self.col_extension,
+				}
+			);
+			Ok(())
+		}
+
+		/// Checks that the commitment is consistent with `lookup`, the data lookup of its header.
+		pub fn validate(&self, lookup: &DataLookup) -> Result<(), ExtensionError> {
+			validate_lookup(self.rows, self.cols, &self.commitment, lookup)?;
+			self.validate_extension()?;
+			self.validate_commitment()
+		}
+	}
+
+	impl fmt::Debug for KateCommitment {
+		fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+			let commitment: &[u8] = self.commitment.as_slice();
+			let data_root: &[u8] = self.data_root.as_ref();
+
+			f.debug_struct("KateCommitment(v4)")
+				.field("rows", &self.rows)
+				.field("cols", &self.cols)
+				.field("row_extension", &self.row_extension)
+				.field("col_extension", &self.col_extension)
+				.field("commitment", &HexDisplay(commitment))
+				.field("data_root", &HexDisplay(data_root))
+				.finish()
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::AppId;
+	use test_case::test_case;
+
+	fn lookup(len: u32) -> DataLookup {
+		DataLookup::from_id_and_len_iter([(AppId(0), len)].into_iter()).unwrap()
+	}
+
+	#[test_case(2, 4, lookup(8), 192 => Ok(()); "full matrix")]
+	#[test_case(2, 4, lookup(5), 192 => Ok(()); "padded matrix")]
+	#[test_case(0, 0, DataLookup::new_empty(), 0 => Ok(()); "empty")]
+	#[test_case(0, 0, DataLookup::new_error(), 0 => Ok(()); "faulty")]
+	#[test_case(2, 4, lookup(9), 192 => Err(ExtensionError::LookupTooLarge { lookup_len: 9, rows: 2, cols: 4 }); "lookup too large")]
+	#[test_case(2, 4, lookup(8), 96 => Err(ExtensionError::InvalidCommitmentLength { expected: 192, actual: 96 }); "original rows only")]
+	#[test_case(2, 4, lookup(8), 191 => Err(ExtensionError::InvalidCommitmentLength { expected: 192, actual: 191 }); "truncated commitment")]
+	#[test_case(0, 0, DataLookup::new_empty(), 48 => Err(ExtensionError::InvalidCommitmentLength { expected: 0, actual: 48 }); "commitment without rows")]
+	#[test_case(2, 4, DataLookup::new_error(), 192 => Err(ExtensionError::NonEmptyFaulty); "faulty with rows")]
+	fn validate_v3(
+		rows: u16,
+		cols: u16,
+		lookup: DataLookup,
+		commitment_len: usize,
+	) -> Result<(), ExtensionError> {
+		let commitment = vec![0u8; commitment_len];
+		v3::KateCommitment::new(rows, cols, H256::zero(), commitment).validate(&lookup)
+	}
+
+	#[test_case(2, 4, (2, 2), lookup(8), 96 => Ok(()); "original rows")]
+	#[test_case(2, 4, (1, 4), lookup(8), 96 => Ok(()); "rows only extension")]
+	#[test_case(0, 0, (0, 0), DataLookup::new_empty(), 0 => Ok(()); "empty")]
+	#[test_case(0, 0, (0, 0), DataLookup::new_error(), 0 => Ok(()); "faulty")]
+	#[test_case(2, 4, (2, 2), lookup(8), 192 => Err(ExtensionError::InvalidCommitmentLength { expected: 96, actual: 192 }); "extended rows")]
+	#[test_case(1, 4, (2, 2), lookup(5), 48 => Err(ExtensionError::LookupTooLarge { lookup_len: 5, rows: 1, cols: 4 }); "lookup too large")]
+	#[test_case(2, 4, (0, 0), lookup(8), 96 => Err(ExtensionError::InvalidExtensionFactors { row_extension: 0, col_extension: 0 }); "no extension")]
+	#[test_case(2, 4, (2, 0), lookup(8), 96 => Err(ExtensionError::InvalidExtensionFactors { row_extension: 2, col_extension: 0 }); "zero col extension")]
+	#[test_case(2, 4, (3, 2), lookup(8), 96 => Err(ExtensionError::InvalidExtensionFactors { row_extension: 3, col_extension: 2 }); "row extension not power of two")]
+	#[test_case(2, 4, (2, 6), lookup(8), 96 => Err(ExtensionError::InvalidExtensionFactors { row_extension: 2, col_extension: 6 }); "col extension not power of two")]
+	#[test_case(2, 4, (2, 16384), lookup(8), 96 => Err(ExtensionError::InvalidExtensionFactors { row_extension: 2, col_extension: 16384 }); "extended cols overflow")]
+	#[test_case(0, 0, (2, 2), DataLookup::new_empty(), 0 => Err(ExtensionError::InvalidExtensionFactors { row_extension: 2, col_extension: 2 }); "empty with extension")]
+	fn validate_v4(
+		rows: u16,
+		cols: u16,
+		(row_extension, col_extension): (u16, u16),
+		lookup: DataLookup,
+		commitment_len: usize,
+	) -> Result<(), ExtensionError> {
+		let commitment = vec![0u8; commitment_len];
+		v4::KateCommitment::new(
+			rows,
+			cols,
+			row_extension,
+			col_extension,
+			H256::zero(),
+			commitment,
+		)
+		.validate(&lookup)
+	}
+}
@@ -46,7 +46,7 @@ pub mod keccak256;
 pub use keccak256::Keccak256;
 
 pub mod data_proof;
-pub use data_proof::DataProof;
+pub use data_proof::{DataProof, MultiDataProof};
 
 pub mod data_lookup;
 pub use data_lookup::*;
@@ -0,0 +1,76 @@
+//! Compares committing and proving with the plain trusted setup against [`couscous::PublicParams`].
+//! Both arms only differ with the precomputed table, so the bench requires the `precomputed-srs`
+//! feature: `cargo bench --bench commitments --features precomputed-srs`.
+
+use avail_core::{AppExtrinsic, AppId, BlockLengthColumns, BlockLengthRows};
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use kate::{
+	com::Cell,
+	couscous,
+	gridgen::core::{EvaluationGrid, PolynomialGrid},
+	Seed,
+};
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaChaRng;
+
+const COLS: [usize; 3] = [32, 256, 1024];
+const MAX_ROWS: usize = 256;
+
+/// Evaluation grid of `cols` columns, with about 8 rows of data.
+fn evaluation_grid(cols: usize) -> EvaluationGrid {
+	let mut rng = ChaChaRng::from_seed([42u8; 32]);
+	let data = (0..cols * 8 * 31).map(|_| rng.gen()).collect::<Vec<u8>>();
+	let xts = vec![AppExtrinsic::new(AppId(1), data)];
+	EvaluationGrid::from_extrinsics(xts, 4, cols, MAX_ROWS, Seed::default()).unwrap()
+}
+
+fn bench_public_params(c: &mut Criterion) {
+	let mut group = c.benchmark_group("public params");
+	group.bench_function("multiproof_params", |b| b.iter(couscous::multiproof_params));
+	group.bench_function("public_params", |b| b.iter(couscous::public_params));
+	group.finish();
+}
+
+fn bench_commit(c: &mut Criterion) {
+	let params = couscous::multiproof_params();
+	let public_params = couscous::public_params();
+
+	let mut group = c.benchmark_group("commit");
+	group.sample_size(10);
+	for cols in COLS {
+		let grid: PolynomialGrid = evaluation_grid(cols).make_polynomial_grid().unwrap();
+		group.bench_with_input(
+			BenchmarkId::new("multiproof_params", cols),
+			&grid,
+			|b, grid| b.iter(|| grid.commitments(&params).unwrap()),
+		);
+		group.bench_with_input(BenchmarkId::new("public_params", cols), &grid, |b, grid| {
+			b.iter(|| grid.commitments(public_params).unwrap())
+		});
+	}
+	group.finish();
+}
+
+fn bench_prove(c: &mut Criterion) {
+	let params = couscous::multiproof_params();
+	let public_params = couscous::public_params();
+
+	let mut group = c.benchmark_group("prove");
+	for cols in COLS {
+		let grid = evaluation_grid(cols).make_polynomial_grid().unwrap();
+		let cell = Cell::new(BlockLengthRows(0), BlockLengthColumns(1));
+
+		group.bench_with_input(
+			BenchmarkId::new("multiproof_params", cols),
+			&grid,
+			|b, grid| b.iter(|| grid.proof(&params, &cell).unwrap()),
+		);
+		group.bench_with_input(BenchmarkId::new("public_params", cols), &grid, |b, grid| {
+			b.iter(|| grid.proof_with_public_params(public_params, &cell).unwrap())
+		});
+	}
+	group.finish();
+}
+
+criterion_group! { benches, bench_public_params, bench_commit, bench_prove }
+criterion_main!(benches);
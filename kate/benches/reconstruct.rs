@@ -7,7 +7,7 @@ use kate_recovery::{
 	commitments,
 	commons::ArkScalar,
 	data::{self, DataCell},
-	matrix::Position,
+	matrix::{Dimensions, Position},
 	proof, testnet,
 };
 use nalgebra::DMatrix;
@@ -163,8 +163,9 @@ fn reconstruct(xts: &[AppExtrinsic]) {
 			content: proof.try_into().unwrap(),
 		};
 
-		let extended_dims = dims.try_into().unwrap();
-		let commitment = commitments::from_slice(&commitments).unwrap()[row as usize];
+		let extended_dims: Dimensions = dims.try_into().unwrap();
+		let ext_rows = usize::try_from(extended_dims.extended_rows()).unwrap();
+		let commitment = commitments::from_slice(&commitments, ext_rows).unwrap()[row as usize];
 		let verification = proof::verify_v2(&public_params, extended_dims, &commitment, &cell);
 		assert!(verification.is_ok());
 		assert!(verification.unwrap());
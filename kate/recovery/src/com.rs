@@ -20,12 +20,20 @@ use codec::{Decode, IoReader};
 #[cfg(feature = "std")]
 pub use num_traits::{One, Zero};
 #[cfg(feature = "std")]
-use poly_multiproof::{ark_ff::Field, ark_poly::EvaluationDomain, traits::AsBytes};
+use poly_multiproof::{
+	ark_ff::{batch_inversion, Field},
+	ark_poly::{univariate::DensePolynomial, DenseUVPolynomial, EvaluationDomain},
+	traits::AsBytes,
+};
+#[cfg(feature = "std")]
+use rand::Rng;
+#[cfg(feature = "std")]
+use rand_chacha::{rand_core::SeedableRng, ChaChaRng};
 #[cfg(feature = "std")]
 use static_assertions::{const_assert, const_assert_ne};
 #[cfg(feature = "std")]
 use std::{
-	collections::{HashMap, HashSet},
+	collections::{hash_map::Entry, HashMap, HashSet},
 	convert::{TryFrom, TryInto},
 	iter::FromIterator,
 };
@@ -42,7 +50,7 @@ pub enum ReconstructionError {
 	MinCells(usize),
 	#[error("Duplicate cell found")]
 	DuplicateCellFound,
-	#[error("Column {0} contains less than half rows")]
+	#[error("Column {0} contains less rows than required for reconstruction")]
 	InvalidColumn(u16),
 	#[error("Cannot decode data: {0}")]
 	DataDecodingError(#[from] UnflattenError),
@@ -60,6 +68,12 @@ pub enum ReconstructionError {
 	InvalidEvaluationDomain,
 	#[error("Bad zero poly evaluation")]
 	BadZeroPoly,
+	#[error("Missing cells do not match the erasure pattern")]
+	ErasurePatternMismatch,
+	#[error("Data length does not match the dimensions")]
+	InvalidDataLength,
+	#[error("Padding does not match the seed at cell ({0})")]
+	PaddingMismatch(matrix::Position),
 }
 
 #[cfg(feature = "std")]
@@ -224,6 +238,7 @@ pub fn reconstruct_columns(
 
 	// Map cells by column
 	let columns = map_cells(dimensions, data_cells)?;
+	let domain = RecoveryDomain::new(dimensions.extension(), dimensions.extended_rows())?;
 
 	// Reconstruct each column
 	columns
@@ -239,7 +254,9 @@ pub fn reconstruct_columns(
 			let cell_values = cells.values().cloned().collect::<Vec<_>>();
 
 			// Reconstruct the column
-			let column = reconstruct_column(dimensions.extended_rows(), &cell_values)?
+			let subset = column_subset(&domain, &cell_values)?;
+			let column = domain
+				.reconstruct(&subset)?
 				.iter()
 				.map(ArkScalar::to_bytes)
 				.collect::<Result<Vec<[u8; CHUNK_SIZE]>, _>>()
@@ -250,29 +267,50 @@ pub fn reconstruct_columns(
 		.collect::<Result<_, _>>()
 }
 
+/// Reconstructs the data matrix, row by row, as `CHUNK_SIZE` bytes per cell.
+/// Cells of columns without any given cell are zeroed.
+///
+/// # Arguments
+///
+/// * `dimensions` - Extended matrix dimensions
+/// * `cells` - Cells from required columns, at least 50% cells per column
 #[cfg(feature = "std")]
-fn reconstruct_available(
+pub fn reconstruct_available(
 	dimensions: matrix::Dimensions,
 	cells: Vec<data::DataCell>,
 ) -> Result<Vec<u8>, ReconstructionError> {
 	let columns = map_cells(dimensions, cells)?;
 	let rows: usize = dimensions.height();
 
-	let scalars = (0..dimensions.cols().get())
-		.map(|col| match columns.get(&col) {
-			None => Ok(vec![None; rows]),
-			Some(column_cells) => {
-				ensure!(
-					column_cells.len() >= rows,
-					ReconstructionError::InvalidColumn(col)
-				);
-				let cells = column_cells.values().cloned().collect::<Vec<_>>();
-
-				reconstruct_column(dimensions.extended_rows(), &cells)
-					.map(|scalars| scalars.into_iter().map(Some).collect::<Vec<_>>())
+	let data_col = |col| dimensions.extended_data_col(col);
+	let domain = RecoveryDomain::new(dimensions.extension(), dimensions.extended_rows())?;
+	// Columns missing the same rows share their vanishing polynomial
+	let mut patterns: HashMap<Vec<bool>, ErasurePattern> = HashMap::new();
+
+	let mut scalars = Vec::with_capacity(dimensions.cols().get().into());
+	for col in 0..dimensions.cols().get() {
+		let Some(column_cells) = columns.get(&data_col(col)) else {
+			scalars.push(vec![None; rows]);
+			continue;
+		};
+		ensure!(
+			column_cells.len() >= rows,
+			ReconstructionError::InvalidColumn(col)
+		);
+		let cells = column_cells.values().cloned().collect::<Vec<_>>();
+		let subset = column_subset(&domain, &cells)?;
+
+		let missing = subset.iter().map(Option::is_none).collect::<Vec<_>>();
+		let pattern = match patterns.entry(missing) {
+			Entry::Occupied(entry) => entry.into_mut(),
+			Entry::Vacant(entry) => {
+				let pattern = domain.erasure_pattern(entry.key())?;
+				entry.insert(pattern)
 			},
-		})
-		.collect::<Result<Vec<Vec<_>>, ReconstructionError>>()?;
+		};
+		let column = pattern.recover(&subset)?;
+		scalars.push(column.into_iter().map(Some).collect::<Vec<_>>());
+	}
 
 	let mut result: Vec<u8> = Vec::with_capacity(scalars.len() * CHUNK_SIZE);
 
@@ -291,6 +329,46 @@ fn reconstruct_available(
 	Ok(result)
 }
 
+/// Verifies that the cells after the application data are the random padding generated from
+/// `seed`, so no data can be hidden in the padding of the block.
+/// The first cell which does not match is reported by its position in the extended matrix.
+///
+/// # Arguments
+///
+/// * `lookup` - Application data index
+/// * `dimensions` - Extended matrix dimensions
+/// * `data` - Data matrix, row by row, as `CHUNK_SIZE` bytes per cell (see `reconstruct_available`)
+/// * `seed` - Seed used to generate the padding of the block
+#[cfg(feature = "std")]
+pub fn verify_padding(
+	lookup: &DataLookup,
+	dimensions: matrix::Dimensions,
+	data: &[u8],
+	seed: [u8; 32],
+) -> Result<(), ReconstructionError> {
+	let cells = data.chunks_exact(CHUNK_SIZE);
+	let data_cells =
+		usize::try_from(lookup.len()).map_err(|_| ReconstructionError::InvalidDataLength)?;
+	ensure!(
+		cells.remainder().is_empty()
+			&& cells.len() == dimensions.size::<usize>()
+			&& data_cells <= cells.len(),
+		ReconstructionError::InvalidDataLength
+	);
+
+	const_assert!(DATA_CHUNK_SIZE < CHUNK_SIZE);
+	let mut rng = ChaChaRng::from_seed(seed);
+	for (cell, chunk) in (0u32..).zip(cells).skip(data_cells) {
+		let padding: [u8; DATA_CHUNK_SIZE] = rng.gen();
+		let (random, zeroes) = chunk.split_at(DATA_CHUNK_SIZE);
+		ensure!(
+			random == padding && zeroes.iter().all(|&byte| byte == 0),
+			ReconstructionError::PaddingMismatch(dimensions.extended_data_position(cell))
+		);
+	}
+	Ok(())
+}
+
 /// Decode app extrinsics from extrinsics layout and data cells.
 /// Only related data cells are needed, without erasure coded data.
 ///
@@ -402,96 +480,188 @@ pub fn unflatten_padded_data(
 
 // This module is taken from https://gist.github.com/itzmeanjan/4acf9338d9233e79cfbee5d311e7a0b4
 // which I wrote few months back when exploring polynomial based erasure coding technique !
+/// Evaluation domain of an extended column, along with its roots of unity.
+/// Roots are computed once, so every column recovered over the same domain reuses them.
 #[cfg(feature = "std")]
-fn reconstruct_poly(
-	// domain I'm working with
-	// all (i)ffts to be performed on it
+pub struct RecoveryDomain {
 	eval_domain: ArkEvaluationDomain,
-	// subset of available data
-	subset: Vec<Option<ArkScalar>>,
-) -> Result<Vec<ArkScalar>, ReconstructionError> {
-	let missing_indices = subset
-		.iter()
-		.enumerate()
-		.filter(|e| e.1.is_none())
-		.map(|(i, _)| i as u64)
-		.collect::<Vec<_>>();
-	let (mut zero_poly, zero_eval) =
-		zero_poly_fn(eval_domain, missing_indices.as_slice(), subset.len() as u64);
-	for i in 0..subset.len() {
-		if subset[i].is_none() && zero_eval[i] != ArkScalar::zero() {
-			return Err(ReconstructionError::BadZeroPoly);
-		}
+	short_domain: ArkEvaluationDomain,
+	roots: Vec<ArkScalar>,
+}
+
+#[cfg(feature = "std")]
+impl RecoveryDomain {
+	/// Creates the domain of an extended column of `row_count` cells.
+	pub fn new(
+		extension: matrix::ExtensionConfig,
+		row_count: u32,
+	) -> Result<Self, ReconstructionError> {
+		let row_count =
+			usize::try_from(row_count).map_err(|_| ReconstructionError::RowCountExceeded)?;
+		let eval_domain = ArkEvaluationDomain::new(row_count)
+			.ok_or(ReconstructionError::InvalidEvaluationDomain)?;
+		ensure!(
+			eval_domain.size() == row_count,
+			ReconstructionError::InvalidRowCount
+		);
+
+		Self::from_domain(eval_domain, extension.row_factor().get().into())
 	}
-	let mut poly_evals_with_zero: Vec<ArkScalar> = Vec::new();
-	for i in 0..subset.len() {
-		if let Some(v) = subset[i] {
-			poly_evals_with_zero.push(v * zero_eval[i]);
-		} else {
-			poly_evals_with_zero.push(ArkScalar::zero());
-		}
+
+	fn from_domain(
+		eval_domain: ArkEvaluationDomain,
+		row_factor: usize,
+	) -> Result<Self, ReconstructionError> {
+		ensure!(
+			row_factor != 0 && eval_domain.size() % row_factor == 0,
+			ReconstructionError::InvalidRowCount
+		);
+		let short_domain = ArkEvaluationDomain::new(eval_domain.size() / row_factor)
+			.ok_or(ReconstructionError::InvalidEvaluationDomain)?;
+		let roots = eval_domain.elements().collect();
+
+		Ok(Self {
+			eval_domain,
+			short_domain,
+			roots,
+		})
 	}
-	let mut poly_with_zero = eval_domain.ifft(&poly_evals_with_zero[..]);
-	shift_poly(&mut poly_with_zero[..]);
-	shift_poly(&mut zero_poly[..]);
-	let mut eval_shifted_poly_with_zero = eval_domain.fft(&poly_with_zero[..]);
-	let eval_shifted_zero_poly = eval_domain.fft(&zero_poly[..]);
-	for i in 0..eval_shifted_poly_with_zero.len() {
-		eval_shifted_poly_with_zero[i] *= eval_shifted_zero_poly[i]
-			.inverse()
-			.ok_or(ReconstructionError::BadZeroPoly)?;
+
+	/// Number of cells of the extended column.
+	pub fn size(&self) -> usize {
+		self.eval_domain.size()
 	}
 
-	let mut shifted_reconstructed_poly = eval_domain.ifft(&eval_shifted_poly_with_zero[..]);
-	unshift_poly(&mut shifted_reconstructed_poly[..]);
+	/// Minimum number of cells needed to reconstruct the column.
+	pub fn min_cells(&self) -> usize {
+		self.short_domain.size()
+	}
 
-	let short_domain = ArkEvaluationDomain::new(eval_domain.size() / 2).unwrap();
+	/// Prepares the recovery of any column missing the rows marked in `missing`.
+	pub fn erasure_pattern(
+		&self,
+		missing: &[bool],
+	) -> Result<ErasurePattern<'_>, ReconstructionError> {
+		ensure!(
+			missing.len() == self.size(),
+			ReconstructionError::ErasurePatternMismatch
+		);
+		let missing_roots = missing
+			.iter()
+			.zip(self.roots.iter())
+			.filter(|(is_missing, _)| **is_missing)
+			.map(|(_, root)| *root)
+			.collect::<Vec<_>>();
+		let available = self.size().saturating_sub(missing_roots.len());
+		ensure!(
+			available >= self.min_cells(),
+			ReconstructionError::MinCells(self.min_cells())
+		);
+
+		let mut zero_poly = vanishing_poly(&missing_roots);
+		zero_poly.resize(self.size(), ArkScalar::zero());
+		let zero_eval = self.eval_domain.fft(&zero_poly);
+		let zero_on_missing = missing
+			.iter()
+			.zip(zero_eval.iter())
+			.all(|(is_missing, eval)| !is_missing || eval.is_zero());
+		ensure!(zero_on_missing, ReconstructionError::BadZeroPoly);
+
+		shift_poly(&mut zero_poly[..]);
+		let mut inv_shifted_zero_eval = self.eval_domain.fft(&zero_poly);
+		ensure!(
+			inv_shifted_zero_eval.iter().all(|eval| !eval.is_zero()),
+			ReconstructionError::BadZeroPoly
+		);
+		batch_inversion(&mut inv_shifted_zero_eval);
+
+		Ok(ErasurePattern {
+			domain: self,
+			missing: missing.to_vec(),
+			zero_eval,
+			inv_shifted_zero_eval,
+		})
+	}
 
-	let reconstructed_data = short_domain.fft(&shifted_reconstructed_poly[..]);
-	Ok(reconstructed_data)
+	/// Reconstructs the data rows of a column, where `None` marks a missing cell.
+	pub fn reconstruct(
+		&self,
+		subset: &[Option<ArkScalar>],
+	) -> Result<Vec<ArkScalar>, ReconstructionError> {
+		let missing = subset.iter().map(Option::is_none).collect::<Vec<_>>();
+		self.erasure_pattern(&missing)?.recover(subset)
+	}
 }
 
+/// Missing rows of an extended column, along with the evaluations of their vanishing polynomial.
+/// Columns missing the same rows are recovered using the same pattern.
 #[cfg(feature = "std")]
-fn expand_root_of_unity(eval_domain: ArkEvaluationDomain) -> Vec<ArkScalar> {
-	let root_of_unity = eval_domain.group_gen();
-	let mut roots: Vec<ArkScalar> = vec![ArkScalar::one(), root_of_unity];
-	let mut i = 1;
-	while roots[i] != ArkScalar::one() {
-		roots.push(roots[i] * root_of_unity);
-		i += 1;
-	}
-	roots
+pub struct ErasurePattern<'a> {
+	domain: &'a RecoveryDomain,
+	missing: Vec<bool>,
+	zero_eval: Vec<ArkScalar>,
+	inv_shifted_zero_eval: Vec<ArkScalar>,
 }
 
 #[cfg(feature = "std")]
-fn zero_poly_fn(
-	eval_domain: ArkEvaluationDomain,
-	missing_indices: &[u64],
-	length: u64,
-) -> (Vec<ArkScalar>, Vec<ArkScalar>) {
-	let expanded_r_o_u = expand_root_of_unity(eval_domain);
-	let domain_stride = eval_domain.size() as u64 / length;
-	let mut zero_poly: Vec<ArkScalar> = Vec::with_capacity(length as usize);
-	let mut sub: ArkScalar;
-	for i in 0..missing_indices.len() {
-		let v = missing_indices[i];
-		sub = ArkScalar::zero() - expanded_r_o_u[(v * domain_stride) as usize];
-		zero_poly.push(sub);
-		if i > 0 {
-			zero_poly[i] = zero_poly[i] + zero_poly[i - 1];
-			for j in (1..i).rev() {
-				zero_poly[j] *= sub;
-				zero_poly[j] = zero_poly[j] + zero_poly[j - 1];
+impl ErasurePattern<'_> {
+	/// Recovers the data rows of a column, which must miss exactly the rows of this pattern.
+	pub fn recover(
+		&self,
+		subset: &[Option<ArkScalar>],
+	) -> Result<Vec<ArkScalar>, ReconstructionError> {
+		let same_pattern = subset.len() == self.missing.len()
+			&& subset
+				.iter()
+				.zip(self.missing.iter())
+				.all(|(cell, is_missing)| cell.is_none() == *is_missing);
+		ensure!(same_pattern, ReconstructionError::ErasurePatternMismatch);
+
+		let eval_domain = &self.domain.eval_domain;
+		let poly_evals_with_zero = subset
+			.iter()
+			.zip(self.zero_eval.iter())
+			.map(|(cell, zero)| cell.map_or_else(ArkScalar::zero, |value| value * zero))
+			.collect::<Vec<_>>();
+		let mut poly_with_zero = eval_domain.ifft(&poly_evals_with_zero);
+		shift_poly(&mut poly_with_zero[..]);
+
+		let mut eval_shifted_poly = eval_domain.fft(&poly_with_zero);
+		eval_shifted_poly
+			.iter_mut()
+			.zip(self.inv_shifted_zero_eval.iter())
+			.for_each(|(eval, inv_zero)| *eval *= inv_zero);
+
+		let mut shifted_reconstructed_poly = eval_domain.ifft(&eval_shifted_poly);
+		unshift_poly(&mut shifted_reconstructed_poly[..]);
+
+		Ok(self.domain.short_domain.fft(&shifted_reconstructed_poly))
+	}
+}
+
+/// Builds the vanishing polynomial `(x - r_0) * ... * (x - r_n)` of `roots` using a subproduct
+/// tree, where halves are multiplied through FFTs. Small subtrees are multiplied directly.
+#[cfg(feature = "std")]
+fn vanishing_poly(roots: &[ArkScalar]) -> Vec<ArkScalar> {
+	const DIRECT_PRODUCT_MAX_ROOTS: usize = 64;
+
+	if roots.len() <= DIRECT_PRODUCT_MAX_ROOTS {
+		let mut coeffs = Vec::with_capacity(roots.len() + 1);
+		coeffs.push(ArkScalar::one());
+		for root in roots {
+			coeffs.push(ArkScalar::zero());
+			for j in (1..coeffs.len()).rev() {
+				coeffs[j] = coeffs[j - 1] - coeffs[j] * root;
 			}
-			zero_poly[0] *= sub
+			coeffs[0] = -(coeffs[0] * root);
 		}
+		return coeffs;
 	}
-	zero_poly.push(ArkScalar::one());
-	for _ in zero_poly.len()..zero_poly.capacity() {
-		zero_poly.push(ArkScalar::zero());
-	}
-	let zero_eval = eval_domain.fft(&zero_poly[..]);
-	(zero_poly, zero_eval)
+
+	let (left, right) = roots.split_at(roots.len() / 2);
+	let left = DensePolynomial::from_coefficients_vec(vanishing_poly(left));
+	let right = DensePolynomial::from_coefficients_vec(vanishing_poly(right));
+	(&left * &right).coeffs
 }
 
 // in-place shifting
@@ -527,7 +697,7 @@ fn unshift_poly(poly: &mut [ArkScalar]) {
 pub type AppDataRange = Range<u32>;
 
 // use this function for reconstructing back all cells of certain column
-// when at least 50% of them are available
+// when at least `1 / row_factor` of them are available
 //
 // if everything goes fine, returned vector in case of success should have
 // `row_count`-many cells of some specific column, in coded form
@@ -536,9 +706,42 @@ pub type AppDataRange = Range<u32>;
 // coded together
 #[cfg(feature = "std")]
 pub fn reconstruct_column(
+	extension: matrix::ExtensionConfig,
 	row_count: u32,
 	cells: &[data::DataCell],
 ) -> Result<Vec<ArkScalar>, ReconstructionError> {
+	let domain = RecoveryDomain::new(extension, row_count)?;
+	let subset = column_subset(&domain, cells)?;
+
+	domain.reconstruct(&subset)
+}
+
+/// Reconstructs many columns missing the same rows, building their vanishing polynomial once.
+/// Each column holds `row_count` cells in row order, `None` marking a missing cell.
+#[cfg(feature = "std")]
+pub fn reconstruct_columns_batch(
+	extension: matrix::ExtensionConfig,
+	row_count: u32,
+	columns: &[Vec<Option<ArkScalar>>],
+) -> Result<Vec<Vec<ArkScalar>>, ReconstructionError> {
+	let Some(first) = columns.first() else {
+		return Ok(vec![]);
+	};
+	let domain = RecoveryDomain::new(extension, row_count)?;
+	let missing = first.iter().map(Option::is_none).collect::<Vec<_>>();
+	let pattern = domain.erasure_pattern(&missing)?;
+	columns
+		.iter()
+		.map(|column| pattern.recover(column))
+		.collect()
+}
+
+// orders the cells of a single column by row index, leaving `None` where a cell is missing
+#[cfg(feature = "std")]
+fn column_subset(
+	domain: &RecoveryDomain,
+	cells: &[data::DataCell],
+) -> Result<Vec<Option<ArkScalar>>, ReconstructionError> {
 	// just ensures all rows are from same column !
 	// it's required as that's how it's erasure coded during
 	// construction in validator node
@@ -550,47 +753,38 @@ pub fn reconstruct_column(
 		cells.iter().all(|c| c.position.col == first_col)
 	}
 
-	// given row index in column of interest, finds it if present
-	// and returns back wrapped in `Some`, otherwise returns `None`
-	fn find_row_by_index(idx: u32, cells: &[data::DataCell]) -> Option<ArkScalar> {
-		cells
-			.iter()
-			.find(|cell| cell.position.row == idx)
-			.and_then(|cell| {
-				<[u8; CHUNK_SIZE]>::try_from(&cell.data[..])
-					.map(|data| ArkScalar::from_bytes(&data).ok())
-					.ok()
-					.flatten()
-			})
-	}
-
-	// row count of data matrix must be power of two !
-	let row_count_sz =
-		usize::try_from(row_count).map_err(|_| ReconstructionError::RowCountExceeded)?;
-	ensure!(row_count % 2 == 0, ReconstructionError::InvalidRowCount);
+	let row_count = domain.size();
+	let min_cells = domain.min_cells();
 	ensure!(
-		cells.len() >= row_count_sz / 2,
-		ReconstructionError::MinCells(row_count_sz / 2)
+		cells.len() >= min_cells,
+		ReconstructionError::MinCells(min_cells)
 	);
 	ensure!(
-		cells.len() <= row_count_sz,
-		ReconstructionError::MaxCells(row_count_sz)
+		cells.len() <= row_count,
+		ReconstructionError::MaxCells(row_count)
 	);
 	ensure!(
 		check_cells(cells),
 		ReconstructionError::CellsFromDifferentCols
 	);
 
-	let eval_domain = ArkEvaluationDomain::new(row_count_sz)
-		.ok_or(ReconstructionError::InvalidEvaluationDomain)?;
-	let mut subset: Vec<Option<ArkScalar>> = Vec::with_capacity(row_count_sz);
-
-	// fill up vector in ordered fashion
-	for i in 0..row_count {
-		subset.push(find_row_by_index(i, cells));
+	// fill up vector in ordered fashion, first cell of a row wins
+	let mut subset: Vec<Option<ArkScalar>> = vec![None; row_count];
+	let mut filled = vec![false; row_count];
+	for cell in cells {
+		let Ok(row) = usize::try_from(cell.position.row) else {
+			continue;
+		};
+		if row >= row_count || filled[row] {
+			continue;
+		}
+		filled[row] = true;
+		subset[row] = <[u8; CHUNK_SIZE]>::try_from(&cell.data[..])
+			.ok()
+			.and_then(|data| ArkScalar::from_bytes(&data).ok());
 	}
 
-	reconstruct_poly(eval_domain, subset)
+	Ok(subset)
 }
 
 #[cfg(test)]
@@ -605,9 +799,17 @@ mod tests {
 	use super::*;
 	use crate::{
 		data::DataCell,
-		matrix::{Dimensions, Position},
+		matrix::{Dimensions, ExtensionConfig, Position},
 	};
 
+	fn reconstruct_poly(
+		eval_domain: ArkEvaluationDomain,
+		subset: Vec<Option<ArkScalar>>,
+		row_factor: usize,
+	) -> Result<Vec<ArkScalar>, ReconstructionError> {
+		RecoveryDomain::from_domain(eval_domain, row_factor)?.reconstruct(&subset)
+	}
+
 	#[test_case(0 => vec![0] ; "App 0 spans 2 rows from row 0")]
 	#[test_case(1 => vec![0, 2] ; "App 1 spans 2 rows from row 0")]
 	#[test_case(2 => vec![2] ; "App 2 spans 1 rows from row 2")]
@@ -659,7 +861,7 @@ mod tests {
 		// choose random subset of it ( >= 50% )
 		let (coded_src_subset, _) = random_subset(&coded_src, [42u8; 32]);
 		// reconstruct 100% values from random coded subset
-		let coded_recovered = reconstruct_poly(eval_domain, coded_src_subset).unwrap();
+		let coded_recovered = reconstruct_poly(eval_domain, coded_src_subset, 2).unwrap();
 
 		for i in 0..domain_size {
 			assert_eq!(src[i], coded_recovered[i]);
@@ -689,17 +891,12 @@ mod tests {
 		drop_few(&mut coded_src_subset, available);
 
 		// attempt to reconstruct 100% data from <50 % coded data
-		// I've available
-		let recovered = reconstruct_poly(eval_domain, coded_src_subset).unwrap();
-
-		let mut mismatch_count = 0;
-		for i in 0..domain_size {
-			if coded_src[i] != recovered[i] {
-				mismatch_count += 1;
-			}
-		}
-
-		assert!(mismatch_count > 0);
+		// I've available, which must be rejected instead of returning wrong data
+		let recovered = reconstruct_poly(eval_domain, coded_src_subset, 2);
+		assert!(matches!(
+			recovered,
+			Err(ReconstructionError::MinCells(min)) if min == domain_size
+		));
 	}
 
 	// Context behind following two test cases, where one failure condition
@@ -790,7 +987,7 @@ mod tests {
 		// choose random subset of it ( >= 50% )
 		let (coded_src_subset, _) = random_subset(&coded_src, [42u8; 32]);
 		// reconstruct 100% values from random coded subset
-		let recovered = reconstruct_poly(eval_domain, coded_src_subset).unwrap();
+		let recovered = reconstruct_poly(eval_domain, coded_src_subset, 2).unwrap();
 
 		for i in 0..(domain_size) {
 			assert_eq!(src[i], recovered[i]);
@@ -913,7 +1110,8 @@ mod tests {
 			},
 		];
 
-		let reconstructed = reconstruct_column(row_count, &cells[..]).unwrap();
+		let reconstructed =
+			reconstruct_column(ExtensionConfig::DEFAULT, row_count, &cells[..]).unwrap();
 		for i in 0..domain_size {
 			assert_eq!(
 				coded[i as usize * 2],
@@ -951,7 +1149,8 @@ mod tests {
 			},
 		];
 
-		let reconstructed = reconstruct_column(row_count, &cells[..]).unwrap();
+		let reconstructed =
+			reconstruct_column(ExtensionConfig::DEFAULT, row_count, &cells[..]).unwrap();
 		for i in 0..domain_size {
 			assert_eq!(coded[i as usize * 2], reconstructed[i as usize]);
 		}
@@ -980,7 +1179,8 @@ mod tests {
 			},
 		];
 
-		let reconstructed = reconstruct_column(row_count, &cells[..]).unwrap();
+		let reconstructed =
+			reconstruct_column(ExtensionConfig::DEFAULT, row_count, &cells[..]).unwrap();
 		for i in 0..domain_size {
 			assert_eq!(coded[i as usize * 2], reconstructed[i as usize]);
 		}
@@ -1013,9 +1213,159 @@ mod tests {
 			},
 		];
 
-		let reconstructed = reconstruct_column(row_count, &cells[..]).unwrap();
+		let reconstructed =
+			reconstruct_column(ExtensionConfig::DEFAULT, row_count, &cells[..]).unwrap();
 		for i in 0..domain_size {
 			assert_eq!(coded[i as usize * 2], reconstructed[i as usize]);
 		}
 	}
+
+	#[test]
+	fn reconstruct_column_4x_extension() {
+		let extension = ExtensionConfig::new(4, 1).unwrap();
+		let domain_size = 4usize;
+		let row_count = domain_size * 4;
+
+		let src = (0..domain_size)
+			.map(|i| ArkScalar::from(1u64 << (i + 1)))
+			.collect::<Vec<_>>();
+		let mut coeffs = ArkEvaluationDomain::new(domain_size).unwrap().ifft(&src);
+		coeffs.resize(row_count, ArkScalar::zero());
+		let coded = ArkEvaluationDomain::new(row_count).unwrap().fft(&coeffs);
+
+		// Any 25% of the column is enough to reconstruct it.
+		let cells = [1, 6, 11, 15]
+			.iter()
+			.map(|&row| DataCell {
+				position: Position { row, col: 0 },
+				data: coded[row as usize].to_bytes().unwrap(),
+			})
+			.collect::<Vec<_>>();
+
+		let reconstructed = reconstruct_column(extension, row_count as u32, &cells).unwrap();
+		assert_eq!(reconstructed, src);
+
+		let too_few = reconstruct_column(extension, row_count as u32, &cells[1..]);
+		assert!(matches!(too_few, Err(ReconstructionError::MinCells(4))));
+
+		// The public domain API enforces the same minimum.
+		let domain = RecoveryDomain::new(extension, row_count as u32).unwrap();
+		let mut subset = vec![None; row_count];
+		subset[1] = Some(coded[1]);
+		assert!(matches!(
+			domain.reconstruct(&subset),
+			Err(ReconstructionError::MinCells(4))
+		));
+		let missing = subset.iter().map(Option::is_none).collect::<Vec<_>>();
+		assert!(matches!(
+			domain.erasure_pattern(&missing),
+			Err(ReconstructionError::MinCells(4))
+		));
+	}
+
+	#[test]
+	fn vanishing_poly_matches_naive_product() {
+		let domain = ArkEvaluationDomain::new(256).unwrap();
+		let roots = domain.elements().step_by(3).collect::<Vec<_>>();
+		assert!(roots.len() > 64);
+
+		let naive = roots.iter().fold(
+			DensePolynomial::from_coefficients_vec(vec![ArkScalar::one()]),
+			|acc, root| {
+				let factor = DensePolynomial::from_coefficients_vec(vec![-*root, ArkScalar::one()]);
+				&acc * &factor
+			},
+		);
+		assert_eq!(vanishing_poly(&roots), naive.coeffs);
+	}
+
+	#[test]
+	fn reconstruct_columns_batch_shared_pattern() {
+		let extension = ExtensionConfig::DEFAULT;
+		let (domain_size, row_count) = (128usize, 256usize);
+		let short_domain = ArkEvaluationDomain::new(domain_size).unwrap();
+		let eval_domain = ArkEvaluationDomain::new(row_count).unwrap();
+
+		let mut rng = ChaChaRng::from_seed([7u8; 32]);
+		let sources = (0..4)
+			.map(|_| {
+				(0..domain_size)
+					.map(|_| ArkScalar::from(rng.gen::<u64>()))
+					.collect::<Vec<_>>()
+			})
+			.collect::<Vec<_>>();
+		let missing = (0..row_count)
+			.map(|row| row % 4 == 1 || row % 8 == 2)
+			.collect::<Vec<_>>();
+		let columns = sources
+			.iter()
+			.map(|src| {
+				let mut coeffs = short_domain.ifft(src);
+				coeffs.resize(row_count, ArkScalar::zero());
+				eval_domain
+					.fft(&coeffs)
+					.into_iter()
+					.zip(missing.iter())
+					.map(|(cell, is_missing)| (!is_missing).then_some(cell))
+					.collect::<Vec<_>>()
+			})
+			.collect::<Vec<_>>();
+
+		let recovered = reconstruct_columns_batch(extension, row_count as u32, &columns).unwrap();
+		assert_eq!(recovered, sources);
+
+		let mut mismatched = columns;
+		mismatched[2][3] = None;
+		let res = reconstruct_columns_batch(extension, row_count as u32, &mismatched);
+		assert!(matches!(
+			res,
+			Err(ReconstructionError::ErasurePatternMismatch)
+		));
+	}
+
+	fn padded_data(data_cells: usize, cells: usize, seed: [u8; 32]) -> Vec<u8> {
+		let mut rng = ChaChaRng::from_seed(seed);
+		(0..cells)
+			.flat_map(|cell| {
+				let mut chunk = [0u8; CHUNK_SIZE];
+				if cell < data_cells {
+					chunk[..DATA_CHUNK_SIZE].fill(cell as u8);
+				} else {
+					chunk[..DATA_CHUNK_SIZE].copy_from_slice(&rng.gen::<[u8; DATA_CHUNK_SIZE]>());
+				}
+				chunk
+			})
+			.collect()
+	}
+
+	#[test]
+	fn verify_padding_reports_first_mismatch() {
+		let seed = [7u8; 32];
+		let lookup = DataLookup::from_id_and_len_iter(vec![(AppId(0), 3u32)].into_iter()).unwrap();
+		let dimensions = Dimensions::new(2, 4).unwrap();
+		let data = padded_data(3, 8, seed);
+		assert!(verify_padding(&lookup, dimensions, &data, seed).is_ok());
+
+		// Data cells are not part of the padding.
+		let mut app_data = data.clone();
+		app_data[0] ^= 1;
+		assert!(verify_padding(&lookup, dimensions, &app_data, seed).is_ok());
+
+		// Cell 3 is the first padding cell, at the end of the first row.
+		let res = verify_padding(&lookup, dimensions, &data, [8u8; 32]);
+		assert!(
+			matches!(res, Err(ReconstructionError::PaddingMismatch(p)) if p == Position::new(0, 3))
+		);
+
+		// Cell 5 is on the second data row, which is the third row of the extended matrix.
+		let mut hidden = data.clone();
+		hidden[5 * CHUNK_SIZE + DATA_CHUNK_SIZE] = 1;
+		let res = verify_padding(&lookup, dimensions, &hidden, seed);
+		assert!(
+			matches!(res, Err(ReconstructionError::PaddingMismatch(p)) if p == Position::new(2, 1))
+		);
+
+		let res = verify_padding(&lookup, dimensions, &data[CHUNK_SIZE..], seed);
+		assert!(matches!(res, Err(ReconstructionError::InvalidDataLength)));
+	}
 }
@@ -1,4 +1,9 @@
-use avail_core::constants::kate::COMMITMENT_SIZE;
+use avail_core::{
+	constants::kate::COMMITMENT_SIZE,
+	ensure,
+	kate_commitment::{v3, v4},
+	ExtensionError,
+};
 use core::{array::TryFromSliceError, convert::TryInto, num::TryFromIntError};
 use sp_std::prelude::*;
 use thiserror_no_std::Error;
@@ -10,15 +15,19 @@ use crate::{com, matrix};
 #[cfg(feature = "std")]
 use avail_core::constants::kate::CHUNK_SIZE;
 #[cfg(feature = "std")]
-use avail_core::{ensure, AppId, DataLookup};
+use avail_core::{AppId, DataLookup};
 #[cfg(feature = "std")]
 use core::convert::TryFrom;
 #[cfg(feature = "std")]
 use poly_multiproof::{
+	ark_bls12_381::Bls12_381,
 	ark_poly::EvaluationDomain,
 	traits::{AsBytes, Committer},
 };
 
+#[cfg(feature = "std")]
+type ArkCommitment = poly_multiproof::Commitment<Bls12_381>;
+
 #[derive(Error, Debug)]
 pub enum Error {
 	#[error("Scalar slice error: {0}")]
@@ -41,6 +50,8 @@ pub enum Error {
 	IntError(#[from] TryFromIntError),
 	#[error("Arkworks error")]
 	ArkworksError,
+	#[error("Invalid commitment: {0}")]
+	InvalidCommitment(#[from] ExtensionError),
 }
 
 #[cfg(feature = "std")]
@@ -150,19 +161,65 @@ fn row_index_commitment_verification(
 	Ok(None)
 }
 
-/// Creates vector of exact size commitments, from commitments slice
-pub fn from_slice(source: &[u8]) -> Result<Vec<[u8; COMMITMENT_SIZE]>, TryFromSliceError> {
+/// Creates vector of exact size commitments, from the commitment of the header.
+/// Fails if it does not hold exactly one commitment per row of the extended matrix.
+pub fn from_commitment(
+	commitment: &v3::KateCommitment,
+) -> Result<Vec<[u8; COMMITMENT_SIZE]>, Error> {
+	from_slice(&commitment.commitment, commitment.committed_rows())
+}
+
+/// Creates the commitments of every row of the extended matrix of a V4 header.
+///
+/// V4 headers only hold the commitments of the original rows. Columns are Reed-Solomon
+/// extended, so the commitments of the extended rows are the same extension of the original
+/// commitments, and are derived from the header alone.
+/// Fails if the extension factors of the header are not valid.
+#[cfg(feature = "std")]
+pub fn extend_v4(commitment: &v4::KateCommitment) -> Result<Vec<[u8; COMMITMENT_SIZE]>, Error> {
+	commitment.validate_extension()?;
+	let commitments = from_slice(&commitment.commitment, commitment.committed_rows())?
+		.iter()
+		.map(ArkCommitment::from_bytes)
+		.collect::<Result<Vec<_>, _>>()?;
+	if commitments.is_empty() {
+		return Ok(vec![]);
+	}
+
+	let extended_rows = commitments
+		.len()
+		.saturating_mul(usize::from(commitment.row_extension));
+	ArkCommitment::extend_commitments(&commitments, extended_rows)?
+		.iter()
+		.map(|commitment| commitment.to_bytes().map_err(From::from))
+		.collect()
+}
+
+/// Creates vector of exact size commitments, from the commitments slice of `rows` rows.
+/// Fails if it does not hold exactly one commitment per row.
+pub fn from_slice(source: &[u8], rows: usize) -> Result<Vec<[u8; COMMITMENT_SIZE]>, Error> {
+	let expected = rows.saturating_mul(COMMITMENT_SIZE);
+	ensure!(
+		source.len() == expected,
+		Error::InvalidCommitment(ExtensionError::InvalidCommitmentLength {
+			expected,
+			actual: source.len()
+		})
+	);
 	source
-		.chunks(COMMITMENT_SIZE)
-		.map(TryInto::try_into)
-		.collect::<Result<_, _>>()
+		.chunks_exact(COMMITMENT_SIZE)
+		.map(|commitment| commitment.try_into().map_err(From::from))
+		.collect()
 }
 
 #[cfg(test)]
 mod tests {
-	use super::verify_equality;
+	use super::{verify_equality, Error};
 	use crate::{commons::ArkPublicParams, testnet};
-	use avail_core::{AppId, DataLookup};
+	use avail_core::{
+		constants::kate::COMMITMENT_SIZE, kate_commitment::v3::KateCommitment, AppId, DataLookup,
+		ExtensionError,
+	};
 	use once_cell::sync::Lazy;
 
 	use crate::{commitments, matrix};
@@ -183,11 +240,54 @@ mod tests {
 		.is_err());
 	}
 
+	#[test]
+	fn from_commitment_rejects_truncated_blob() {
+		let commitment = |len| KateCommitment {
+			rows: 2,
+			cols: 4,
+			commitment: vec![0u8; len],
+			..Default::default()
+		};
+		assert_eq!(
+			commitments::from_commitment(&commitment(4 * COMMITMENT_SIZE))
+				.unwrap()
+				.len(),
+			4
+		);
+		for len in [0, 3 * COMMITMENT_SIZE, 4 * COMMITMENT_SIZE - 1] {
+			assert!(matches!(
+				commitments::from_commitment(&commitment(len)),
+				Err(Error::InvalidCommitment(
+					ExtensionError::InvalidCommitmentLength { .. }
+				))
+			));
+		}
+	}
+
+	#[test]
+	fn from_slice_checks_row_count() {
+		let source = vec![0u8; 4 * COMMITMENT_SIZE];
+		assert_eq!(commitments::from_slice(&source, 4).unwrap().len(), 4);
+		assert!(commitments::from_slice(&[], 0).unwrap().is_empty());
+		for (len, rows) in [
+			(4 * COMMITMENT_SIZE, 3),
+			(4 * COMMITMENT_SIZE, 5),
+			(3 * COMMITMENT_SIZE + 1, 4),
+		] {
+			assert!(matches!(
+				commitments::from_slice(&source[..len], rows),
+				Err(Error::InvalidCommitment(
+					ExtensionError::InvalidCommitmentLength { .. }
+				))
+			));
+		}
+	}
+
 	#[test]
 	fn verify_equality_ok() {
 		let commitments_bytes = hex::decode("ad98bf48c8c02ee04638a46cecc767678a41687cd2ae78ec13e2f0f078cf73ec200bd477f4a227f10a2a67acc88d5923805beb51d8b72b6aaae127870e971f457779819f7ae8dbfdcaa65b328a947f9949deae49a298456dee84f7df607f8cd98e7848b4d8e5aad76dc1a70c28e3b51d5f935cdfa95601f8fbc2293ed6fb03448eb4038cc718f6e67b61eb0d6b20f7b88738a3d6f31a808794d0cb5e76c476ac9aa964155b262789cd634aa545e54284950d18de93b2fde52144d03f5de7d32fa6b4182f3a4b150d033a207b366972788d1f3b52e24d1ea985044191d18fba3be181adae85caa96a7a85867460f4fc328de41695227f62d2a0ec8863ba61087258a39557b15c22c3c65c820f5f2cd5533967e08832a0e6bbb9d223c5553cbdd681ff913ffef22d5372bd422d567d3b110c17995f1ec0b335246a0037c91799a9b44fb368ad9259b0d0b959a9fbc38aacb618117e2c3261efd77da544bef8da8d3d6b4c2ead898c0c5fa0ae0d6acea9ee53262c15ef1d9e928e1fc0a5d85c4e78").unwrap();
 
-		let commitments = commitments::from_slice(&commitments_bytes).unwrap();
+		let commitments = commitments::from_slice(&commitments_bytes, 8).unwrap();
 
 		let row_0 = Some(hex::decode("04583c323032322d31312d3234205468752031303a31303e800000000000000004e1224120796f756e67206d61727469616c20617274697374206e616d65640020416c6578204b696464206c6561726e73206f6620612076696c6c61696e20006e616d6564204a616e6b656e207468652047726561742077686f2068617320006465666561746564204b696e67205468756e646572206f6620746865206369007479206f6620526164617869616e20616e642077686f20686173206b69646e0061707065642068697320736f6e2c205072696e63652045676c6520286f722000274967756c27292c20616e642045676c652773206669616e63656520507269006e63657373204c6f72612e20446973636f766572696e6720746861742068650020697320746865206c6f737420736f6e206f66204b696e67205468756e646500722c20416c65782073657473206f757420746f207265736375652074686520006b696e67646f6d2e204f6e206869732071756573742c206865206465666561007473204a616e6b656e27732068656e63686d656e20616e64207265747269650076657320766172696f7573206974656d73207768696368206c656164206869006d20746f77617264204a616e6b656e2077686f6d20686520646566656174730020616e642073656573207475726e656420746f2073746f6e652e20416c65780020726574726965766573207468652063726f776e2c20616e6420746865207000656f706c65206f6620526164617869616e2061726520726573746f7265642000756e64657220746865206e65776c792063726f776e6564204b696e67204567006c652e4120796f756e67206d61727469616c20617274697374206e616d65640020416c6578204b696464206c6561726e73206f6620612076696c6c61696e20006e616d6564204a616e6b656e207468652047726561742077686f2068617320006465666561746564204b696e67205468756e646572206f6620746865206369007479206f6620526164617869616e20616e642077686f20686173206b69646e0061707065642068697320736f6e2c205072696e63652045676c6520286f722000274967756c27292c20616e642045676c652773206669616e63656520507269006e63657373204c6f72612e20446973636f766572696e6720746861742068650020697320746865206c6f737420736f6e206f66204b696e67205468756e646500722c20416c65782073657473206f757420746f207265736375652074686520006b696e67646f6d2e204f6e206869732071756573742c206865206465666561007473204a616e6b656e27732068656e63686d656e20616e64207265747269650076657320766172696f7573206974656d73207768696368206c65616420686900").unwrap());
 
@@ -0,0 +1,187 @@
+//! Fraud proofs for incorrectly erasure coded blocks.
+//!
+//! Row commitments in the header commit to every row of the extended matrix. If the extended rows
+//! are not the column-wise Reed-Solomon extension of the original ones, some column of cells with
+//! valid openings is not a codeword. Any `rows + 1` such cells, which do not lie on a polynomial
+//! of degree less than `rows`, prove the block is badly encoded.
+use crate::{
+	com::{self, ReconstructionError},
+	commitments,
+	commons::{ArkEvaluationDomain, ArkPublicParams, ArkScalar},
+	data::{DataCell, SingleCell},
+	matrix::{Dimensions, Position},
+	proof,
+};
+use avail_core::{constants::kate::COMMITMENT_SIZE, ensure, kate_commitment::v3::KateCommitment};
+use codec::{Decode, Encode};
+use poly_multiproof::{ark_poly::EvaluationDomain, traits::AsBytes};
+use std::{collections::HashSet, convert::TryFrom};
+use thiserror_no_std::Error;
+
+#[derive(Error, Debug)]
+pub enum Error {
+	#[error("Block dimensions are not valid")]
+	InvalidDimensions,
+	#[error("Commitments are not valid")]
+	InvalidCommitments,
+	#[error("Proof must contain {0} cells")]
+	InvalidCellCount(usize),
+	#[error("Cell ({0}) is not a distinct cell of the column")]
+	InvalidCell(Position),
+	#[error("Cell ({0}) opening is not valid")]
+	InvalidOpening(Position),
+	#[error("Cannot verify openings: {0}")]
+	Proof(#[from] proof::Error),
+	#[error("Cannot reconstruct column: {0}")]
+	Reconstruction(#[from] ReconstructionError),
+}
+
+/// Cell of a bad encoding proof.
+#[derive(Encode, Decode, Debug, Clone, PartialEq, Eq)]
+pub struct ProofCell {
+	pub row: u32,
+	/// Cell's KZG opening, followed by its data
+	pub content: [u8; 80],
+}
+
+/// Proof that a column of the extended matrix is not a valid Reed-Solomon codeword.
+/// It holds `rows` cells, which determine the column, followed by one cell disagreeing with them.
+#[derive(Encode, Decode, Debug, Clone, PartialEq, Eq)]
+pub struct BadEncodingProof {
+	pub col: u16,
+	pub cells: Vec<ProofCell>,
+}
+
+impl BadEncodingProof {
+	fn single_cells(&self) -> Vec<SingleCell> {
+		self.cells
+			.iter()
+			.map(|cell| SingleCell::new(Position::new(cell.row, self.col), cell.content))
+			.collect()
+	}
+}
+
+/// Builds a bad encoding proof from cells of a single column, if their valid openings
+/// are not a codeword. Returns `None` if the cells are consistent or there are not enough
+/// cells with valid openings.
+///
+/// # Arguments
+///
+/// * `public_parameters` - Public parameters the openings are generated with
+/// * `dimensions` - Extended matrix dimensions
+/// * `commitments` - Commitments of each row of the extended matrix, as found in the header
+/// * `cells` - Cells of the column, along with their openings
+pub fn build_bad_encoding_proof(
+	public_parameters: &ArkPublicParams,
+	dimensions: Dimensions,
+	commitments: &[[u8; COMMITMENT_SIZE]],
+	cells: &[SingleCell],
+) -> Result<Option<BadEncodingProof>, Error> {
+	let Some(col) = cells.first().map(|cell| cell.position.col) else {
+		return Ok(None);
+	};
+	let verification = proof::verify_batch(public_parameters, dimensions, commitments, cells)?;
+
+	let mut rows = HashSet::new();
+	let valid = cells
+		.iter()
+		.filter(|cell| !verification.invalid.contains(&cell.position))
+		.filter(|cell| rows.insert(cell.position.row))
+		.cloned()
+		.collect::<Vec<_>>();
+	if valid.len() <= dimensions.height() {
+		return Ok(None);
+	}
+
+	let Some(mismatch) = find_mismatch(dimensions, col, &valid)? else {
+		return Ok(None);
+	};
+
+	let cells = valid[..dimensions.height()]
+		.iter()
+		.chain(Some(&valid[mismatch]))
+		.map(|cell| ProofCell {
+			row: cell.position.row,
+			content: cell.content,
+		})
+		.collect();
+	Ok(Some(BadEncodingProof { col, cells }))
+}
+
+/// Verifies a bad encoding proof against the header commitment.
+/// Returns `true` if the proof shows that the block is badly encoded.
+///
+/// # Arguments
+///
+/// * `public_parameters` - Public parameters the openings are generated with
+/// * `commitment` - Kate commitment from the header of the block
+/// * `bad_encoding_proof` - Proof to verify
+pub fn verify_bad_encoding_proof(
+	public_parameters: &ArkPublicParams,
+	commitment: &KateCommitment,
+	bad_encoding_proof: &BadEncodingProof,
+) -> Result<bool, Error> {
+	let dimensions =
+		Dimensions::new(commitment.rows, commitment.cols).ok_or(Error::InvalidDimensions)?;
+	let commitments =
+		commitments::from_commitment(commitment).map_err(|_| Error::InvalidCommitments)?;
+
+	let required = dimensions.height().saturating_add(1);
+	ensure!(
+		bad_encoding_proof.cells.len() == required,
+		Error::InvalidCellCount(required)
+	);
+	let cells = bad_encoding_proof.single_cells();
+	let mut rows = HashSet::new();
+	for cell in &cells {
+		ensure!(
+			dimensions.extended_contains(&cell.position) && rows.insert(cell.position.row),
+			Error::InvalidCell(cell.position)
+		);
+	}
+
+	let verification = proof::verify_batch(public_parameters, dimensions, &commitments, &cells)?;
+	if let Some(&position) = verification.invalid.first() {
+		return Err(Error::InvalidOpening(position));
+	}
+
+	Ok(find_mismatch(dimensions, bad_encoding_proof.col, &cells)?.is_some())
+}
+
+/// Reconstructs the column from its first `rows` cells,
+/// and returns the index of the first of other cells which disagrees with it.
+fn find_mismatch(
+	dimensions: Dimensions,
+	col: u16,
+	cells: &[SingleCell],
+) -> Result<Option<usize>, Error> {
+	let height = dimensions.height();
+	let extended_rows = dimensions.extended_rows();
+	let data_cells = cells[..height]
+		.iter()
+		.cloned()
+		.map(DataCell::from)
+		.collect::<Vec<_>>();
+	let data = com::reconstruct_column(dimensions.extension(), extended_rows, &data_cells)?;
+
+	let domain = ArkEvaluationDomain::new(height).ok_or(ReconstructionError::InvalidRowCount)?;
+	let extended_domain = usize::try_from(extended_rows)
+		.ok()
+		.and_then(ArkEvaluationDomain::new)
+		.ok_or(ReconstructionError::InvalidRowCount)?;
+	let mut coeffs = domain.ifft(&data);
+	coeffs.resize(extended_domain.size(), ArkScalar::from(0u64));
+	let column = extended_domain.fft(&coeffs);
+
+	for (index, cell) in cells.iter().enumerate().skip(height) {
+		ensure!(cell.position.col == col, Error::InvalidCell(cell.position));
+		let value = ArkScalar::from_bytes(&cell.data())
+			.map_err(|_| Error::InvalidOpening(cell.position))?;
+		let row =
+			usize::try_from(cell.position.row).map_err(|_| Error::InvalidCell(cell.position))?;
+		if column.get(row) != Some(&value) {
+			return Ok(Some(index));
+		}
+	}
+	Ok(None)
+}
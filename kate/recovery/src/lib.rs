@@ -3,9 +3,13 @@
 pub mod com;
 pub mod commitments;
 pub mod data;
+#[cfg(feature = "std")]
+pub mod fraud;
 pub mod matrix;
 pub mod proof;
 #[cfg(feature = "std")]
+pub mod sampling;
+#[cfg(feature = "std")]
 pub mod sparse_slice_read;
 
 pub mod testnet;
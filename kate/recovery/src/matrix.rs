@@ -61,9 +61,63 @@ impl Position {
 		format!("{}:{}", block_number, self)
 	}
 
-	/// Checks if position is from extended row
+	/// Checks if position is from extended row, using the default extension.
 	pub fn is_extended(&self) -> bool {
-		self.row % 2 == 1
+		self.is_extended_with(ExtensionConfig::DEFAULT)
+	}
+
+	/// Checks if position is from extended row or column of the given extension.
+	pub fn is_extended_with(&self, extension: ExtensionConfig) -> bool {
+		self.row % u32::from(extension.row_factor.get()) != 0
+			|| self.col % extension.col_factor.get() != 0
+	}
+}
+
+/// Erasure coding rate of the extended matrix.
+/// Columns are extended by `row_factor` and rows by `col_factor`, so original data is placed on
+/// every `row_factor`-th row and every `col_factor`-th column of the extended matrix.
+/// Both factors are powers of two, because of the FFT functions requirements.
+#[derive(Copy, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ExtensionConfig {
+	row_factor: NonZeroU16,
+	col_factor: NonZeroU16,
+}
+
+impl ExtensionConfig {
+	/// Columns extended by `EXTENSION_FACTOR`, rows are not extended.
+	pub const DEFAULT: Self = Self {
+		row_factor: unsafe { NonZeroU16::new_unchecked(EXTENSION_FACTOR as u16) },
+		col_factor: NonZeroU16::MIN,
+	};
+
+	/// Creates a new extension config, if both factors are powers of two.
+	pub fn new(row_factor: u16, col_factor: u16) -> Option<Self> {
+		let row_factor = NonZeroU16::new(row_factor).filter(|f| f.is_power_of_two())?;
+		let col_factor = NonZeroU16::new(col_factor).filter(|f| f.is_power_of_two())?;
+
+		Some(Self {
+			row_factor,
+			col_factor,
+		})
+	}
+
+	/// Extension factor of the columns.
+	#[inline]
+	pub const fn row_factor(&self) -> NonZeroU16 {
+		self.row_factor
+	}
+
+	/// Extension factor of the rows.
+	#[inline]
+	pub const fn col_factor(&self) -> NonZeroU16 {
+		self.col_factor
+	}
+}
+
+impl Default for ExtensionConfig {
+	fn default() -> Self {
+		Self::DEFAULT
 	}
 }
 
@@ -87,8 +141,8 @@ impl RowIndex {
 	}
 }
 
-/// Dimensions of a non-extended matrix.
-/// Extended matrix (with factor of 2) is a matrix where data is in odd rows and even rows contains erasure codes.
+/// Dimensions of a non-extended matrix, along with the extension used to build the extended one.
+/// Extended matrix (with default factor of 2) is a matrix where data is in even rows and odd rows contains erasure codes.
 /// Matrix is represented as list of cells, concatenated column by column, which is optimized for erasure coding.
 /// Data is stored in matrix row-wise, which means that accessing data is not optimal.
 /// Dimensions struct provides functions to index specific data cells and rows in matrix representation.
@@ -105,6 +159,7 @@ impl RowIndex {
 pub struct Dimensions {
 	rows: NonZeroU16,
 	cols: NonZeroU16,
+	extension: ExtensionConfig,
 }
 
 impl<R, C> From<(R, C)> for Dimensions
@@ -117,6 +172,7 @@ where
 		Self {
 			rows: rows.into(),
 			cols: cols.into(),
+			extension: ExtensionConfig::DEFAULT,
 		}
 	}
 }
@@ -136,7 +192,11 @@ impl Dimensions {
 		let rows = rows.try_into().ok()?;
 		let cols = cols.try_into().ok()?;
 
-		Some(Self { rows, cols })
+		Some(Self {
+			rows,
+			cols,
+			extension: ExtensionConfig::DEFAULT,
+		})
 	}
 
 	pub fn new_from<R: TryInto<u16>, C: TryInto<u16>>(rows: R, cols: C) -> Option<Self> {
@@ -155,9 +215,23 @@ impl Dimensions {
 		Self {
 			rows: NonZeroU16::new_unchecked(rows),
 			cols: NonZeroU16::new_unchecked(cols),
+			extension: ExtensionConfig::DEFAULT,
 		}
 	}
 
+	/// Returns the same dimensions, extended by `extension`.
+	/// Returns `None` if the extended columns do not fit into `u16`.
+	pub fn with_extension(self, extension: ExtensionConfig) -> Option<Self> {
+		self.cols.checked_mul(extension.col_factor)?;
+		Some(Self { extension, ..self })
+	}
+
+	/// Returns the extension of the matrix.
+	#[inline]
+	pub fn extension(&self) -> ExtensionConfig {
+		self.extension
+	}
+
 	/// Returns number of rows
 	#[inline]
 	pub fn rows(&self) -> NonZeroU16 {
@@ -202,12 +276,12 @@ impl Dimensions {
 		let rows = self.rows.checked_mul(row_factor)?;
 		let cols = self.cols.checked_mul(col_factor)?;
 
-		Some(Self { rows, cols })
+		Self::new(rows, cols)?.with_extension(self.extension)
 	}
 
 	/// Extended matrix size.
 	pub fn extended_size(&self) -> u32 {
-		self.extended_rows() * u32::from(self.cols.get())
+		self.extended_rows() * u32::from(self.extended_cols())
 	}
 
 	/// Row size in bytes
@@ -217,7 +291,16 @@ impl Dimensions {
 
 	/// Extended matrix rows count.
 	pub fn extended_rows(&self) -> u32 {
-		u32::from(self.rows.get()) * EXTENSION_FACTOR
+		u32::from(self.rows.get()) * self.row_factor()
+	}
+
+	/// Extended matrix columns count.
+	pub fn extended_cols(&self) -> u16 {
+		self.cols.get() * self.extension.col_factor.get()
+	}
+
+	fn row_factor(&self) -> u32 {
+		self.extension.row_factor.get().into()
 	}
 
 	/// List of data row indexes in the extended matrix.
@@ -231,7 +314,7 @@ impl Dimensions {
 		let last_row = self.extended_data_row(cells.end - 1);
 
 		let data = (first_row..=last_row)
-			.step_by(EXTENSION_FACTOR as usize)
+			.step_by(self.extension.row_factor.get().into())
 			.collect::<Vec<u32>>();
 		Some(data)
 	}
@@ -239,7 +322,7 @@ impl Dimensions {
 	/// SingleCell positions for given column in extended matrix.
 	/// Empty if column index is not valid.
 	pub fn col_positions(&self, col: u16) -> Vec<Position> {
-		if self.cols().get() <= col {
+		if self.extended_cols() <= col {
 			return vec![];
 		}
 		(0..self.extended_rows())
@@ -253,7 +336,7 @@ impl Dimensions {
 		if self.extended_rows() <= row {
 			return vec![];
 		}
-		(0..self.cols().get())
+		(0..self.extended_cols())
 			.map(|col| Position { col, row })
 			.collect::<Vec<_>>()
 	}
@@ -271,15 +354,20 @@ impl Dimensions {
 		(cell % u32::from(self.cols.get())) as u16
 	}
 
+	/// Extended matrix data column index of a column in the data matrix.
+	pub fn extended_data_col(&self, col: u16) -> u16 {
+		col * self.extension.col_factor.get()
+	}
+
 	/// Extended matrix data row index of cell in the data matrix.
 	fn extended_data_row(&self, cell: u32) -> u32 {
-		(cell / u32::from(self.cols.get())) * EXTENSION_FACTOR
+		(cell / u32::from(self.cols.get())) * self.row_factor()
 	}
 
 	/// Extended matrix data position of a cell in the data matrix.
-	fn extended_data_position(&self, cell: u32) -> Position {
+	pub fn extended_data_position(&self, cell: u32) -> Position {
 		Position {
-			col: self.col(cell),
+			col: self.extended_data_col(self.col(cell)),
 			row: self.extended_data_row(cell),
 		}
 	}
@@ -295,7 +383,7 @@ impl Dimensions {
 
 	/// Checks if extended matrix contains given position.
 	pub fn extended_contains(&self, position: &Position) -> bool {
-		position.row < self.extended_rows() && position.col < self.cols.get()
+		position.row < self.extended_rows() && position.col < self.extended_cols()
 	}
 
 	/// Creates iterator over rows in extended matrix.
@@ -321,7 +409,10 @@ impl Dimensions {
 	pub fn iter_extended_data_positions(&self) -> impl Iterator<Item = (u32, u16)> {
 		let rows: u32 = self.rows.get().into();
 		let cols = self.cols.get();
-		(0..rows).flat_map(move |row| (0..cols).map(move |col| (row * EXTENSION_FACTOR, col)))
+		let row_factor = self.row_factor();
+		let col_factor = self.extension.col_factor.get();
+		(0..rows)
+			.flat_map(move |row| (0..cols).map(move |col| (row * row_factor, col * col_factor)))
 	}
 
 	/// Generates cell positions for given block partition
@@ -333,7 +424,7 @@ impl Dimensions {
 		let size = self.extended_size() as f64 / partition.fraction as f64;
 		let start = (size * (partition.number - 1) as f64).floor() as u32;
 		let end = (size * (partition.number as f64)).ceil() as u32;
-		let cols: u32 = self.cols.get().into();
+		let cols: u32 = self.extended_cols().into();
 
 		(start..end).map(move |cell| Position {
 			row: cell / cols,
@@ -362,6 +453,7 @@ impl Dimensions {
 		Self {
 			rows: self.cols,
 			cols: self.rows,
+			extension: self.extension,
 		}
 	}
 }
@@ -371,7 +463,8 @@ mod tests {
 
 	use test_case::test_case;
 
-	use crate::matrix::{Dimensions, Position};
+	use crate::matrix::{Dimensions, ExtensionConfig, Position};
+	use core::num::NonZeroU16;
 
 	use super::Partition;
 
@@ -439,4 +532,52 @@ mod tests {
 			.zip(expected.iter().map(|&(row, col)| Position { row, col }))
 			.for_each(|(position, expected)| assert_eq!(expected, position));
 	}
+
+	#[test_case(0, 1 => None ; "zero row factor")]
+	#[test_case(3, 1 => None ; "row factor not power of two")]
+	#[test_case(2, 0 => None ; "zero col factor")]
+	#[test_case(4, 1 => Some((4, 1)) ; "4x rows")]
+	#[test_case(2, 2 => Some((2, 2)) ; "2x rows and cols")]
+	fn extension_config(row_factor: u16, col_factor: u16) -> Option<(u16, u16)> {
+		ExtensionConfig::new(row_factor, col_factor)
+			.map(|ext| (ext.row_factor().get(), ext.col_factor().get()))
+	}
+
+	#[test]
+	fn extended_data_positions_with_extension() {
+		let extension = ExtensionConfig::new(4, 2).unwrap();
+		let dimensions = Dimensions::new(2, 2)
+			.unwrap()
+			.with_extension(extension)
+			.unwrap();
+
+		assert_eq!(dimensions.extended_rows(), 8);
+		assert_eq!(dimensions.extended_cols(), 4);
+		assert_eq!(dimensions.extended_size(), 32);
+		assert_eq!(dimensions.extended_data_rows(0..4), Some(vec![0, 4]));
+
+		let positions = dimensions
+			.iter_extended_data_positions()
+			.collect::<Vec<_>>();
+		assert_eq!(positions, vec![(0, 0), (0, 2), (4, 0), (4, 2)]);
+		assert!(positions
+			.iter()
+			.all(|&pos| !Position::from(pos).is_extended_with(extension)));
+		assert!(Position::from((2u32, 0u16)).is_extended_with(extension));
+		assert!(Position::from((4u32, 1u16)).is_extended_with(extension));
+	}
+
+	#[test]
+	fn with_extension_checks_extended_cols() {
+		let dimensions = Dimensions::new(4, 4).unwrap();
+		let extension = ExtensionConfig::new(1, 32768).unwrap();
+		assert_eq!(dimensions.with_extension(extension), None);
+
+		let extension = ExtensionConfig::new(1, 8192).unwrap();
+		let extended = dimensions.with_extension(extension).unwrap();
+		assert_eq!(extended.extended_cols(), 32768);
+		assert_eq!(extended.extended_data_col(3), 24576);
+		let cols = NonZeroU16::new(2).unwrap();
+		assert_eq!(extended.extend(NonZeroU16::MIN, cols), None);
+	}
 }
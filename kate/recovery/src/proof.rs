@@ -1,21 +1,27 @@
-use core::convert::TryInto;
+use core::convert::{TryFrom, TryInto};
 use thiserror_no_std::Error;
 
 use crate::commons::ArkScalar;
-use avail_core::constants::kate::COMMITMENT_SIZE;
+use avail_core::{constants::kate::COMMITMENT_SIZE, ensure};
 use poly_multiproof::{
 	ark_bls12_381::{Bls12_381, Fr},
+	ark_ff::{PrimeField, Zero},
 	ark_poly::{EvaluationDomain as ArkEvaluationDomain, GeneralEvaluationDomain},
 	merlin::Transcript,
 	method1::{M1NoPrecomp, Proof as ArkProof},
 	msm::blst::BlstMSMEngine,
 	traits::{AsBytes, KZGProof, PolyMultiProofNoPrecomp},
+	Pairing,
 };
+#[cfg(feature = "std")]
+use rayon::prelude::*;
 use sp_std::vec::Vec;
 type ArkCommitment = poly_multiproof::Commitment<Bls12_381>;
+type G1 = <Bls12_381 as Pairing>::G1;
+type G1Affine = <Bls12_381 as Pairing>::G1Affine;
 use crate::{
 	data::{GCellBlock, SingleCell},
-	matrix::Dimensions,
+	matrix::{Dimensions, Position},
 };
 
 #[derive(Error, Debug)]
@@ -38,6 +44,12 @@ pub enum Error {
 	FailedToExtractCommitments,
 	#[error("Failed to verify proof")]
 	FailedToVerifyProof,
+	#[error("Commitments length is not a multiple of commitment size")]
+	InvalidCommitmentsLength,
+	#[error("Cell block is out of the grid")]
+	InvalidCellBlock,
+	#[error("Expected {expected} evaluations, found {found}")]
+	InvalidEvalsLength { expected: usize, found: usize },
 }
 
 /// Verifies proof for a given cell using arkworks primitives.
@@ -54,7 +66,7 @@ pub fn verify_v2(
 	let value = ArkScalar::from_bytes(&cell.data()).map_err(|_| Error::InvalidData)?;
 
 	// Get the domain point from the cell position
-	let domain_point = GeneralEvaluationDomain::<Fr>::new(dimensions.width())
+	let domain_point = GeneralEvaluationDomain::<Fr>::new(dimensions.extended_cols().into())
 		.ok_or(Error::InvalidDomain)?
 		.element(cell.position.col.into());
 
@@ -66,54 +78,259 @@ pub fn verify_v2(
 		.map_err(|_| Error::InvalidData)
 }
 
+/// Outcome of a batch verification of cells.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BatchVerification {
+	/// Number of cells which passed verification.
+	pub verified: usize,
+	/// Positions of cells which failed verification.
+	pub invalid: Vec<Position>,
+}
+
+impl BatchVerification {
+	/// Returns `true` if all cells passed verification.
+	pub fn is_valid(&self) -> bool {
+		self.invalid.is_empty()
+	}
+}
+
+/// Single cell opening, deserialized and ready to be aggregated.
+struct Opening<'a> {
+	cell: &'a SingleCell,
+	commitment: &'a [u8; COMMITMENT_SIZE],
+	point: Fr,
+	value: Fr,
+	proof: G1Affine,
+	commitment_point: G1Affine,
+}
+
+/// Verifies proofs for given cells at once, aggregating all openings into a single
+/// multi-pairing using a random linear combination derived from the cells (Fiat-Shamir).
+/// If the aggregated check fails, cells are bisected to find the invalid ones.
+///
+/// # Arguments
+///
+/// * `public_parameters` - Public parameters the proofs are generated with
+/// * `dimensions` - Extended matrix dimensions
+/// * `commitments` - Commitments of each row of the extended matrix
+/// * `cells` - Cells to verify
+pub fn verify_batch(
+	public_parameters: &M1NoPrecomp<Bls12_381, BlstMSMEngine>,
+	dimensions: Dimensions,
+	commitments: &[[u8; COMMITMENT_SIZE]],
+	cells: &[SingleCell],
+) -> Result<BatchVerification, Error> {
+	let domain = GeneralEvaluationDomain::<Fr>::new(dimensions.extended_cols().into())
+		.ok_or(Error::InvalidDomain)?;
+
+	let mut result = BatchVerification::default();
+	let mut openings = Vec::with_capacity(cells.len());
+	for cell in cells {
+		let col = usize::from(cell.position.col);
+		ensure!(col < domain.size(), Error::InvalidPositionInDomain);
+		let commitment = usize::try_from(cell.position.row)
+			.ok()
+			.and_then(|row| commitments.get(row))
+			.ok_or(Error::InvalidPositionInDomain)?;
+		let commitment_point = ArkCommitment::from_bytes(commitment)
+			.map_err(|_| Error::FailedToExtractCommitments)?
+			.0;
+
+		// Malformed cell content can never verify, so it is reported as invalid cell
+		let value = ArkScalar::from_bytes(&cell.data());
+		let proof = ArkProof::<Bls12_381>::from_bytes(&cell.proof());
+		let (Ok(value), Ok(proof)) = (value, proof) else {
+			result.invalid.push(cell.position);
+			continue;
+		};
+
+		openings.push(Opening {
+			cell,
+			commitment,
+			point: domain.element(col),
+			value,
+			proof: proof.0,
+			commitment_point,
+		});
+	}
+
+	bisect(public_parameters, &openings, &mut result.invalid)?;
+	result
+		.invalid
+		.sort_by_key(|position| (position.row, position.col));
+	result.verified = cells.len().saturating_sub(result.invalid.len());
+
+	Ok(result)
+}
+
+/// Verifies `openings` at once, bisecting them on failure and collecting invalid cell positions.
+fn bisect(
+	public_parameters: &M1NoPrecomp<Bls12_381, BlstMSMEngine>,
+	openings: &[Opening],
+	invalid: &mut Vec<Position>,
+) -> Result<(), Error> {
+	if openings.is_empty() || verify_aggregated(public_parameters, openings)? {
+		return Ok(());
+	}
+
+	if let [opening] = openings {
+		invalid.push(opening.cell.position);
+		return Ok(());
+	}
+
+	let (left, right) = openings.split_at(openings.len() / 2);
+	bisect(public_parameters, left, invalid)?;
+	bisect(public_parameters, right, invalid)
+}
+
+/// Checks `e(sum r^i (C_i - y_i * G + z_i * W_i), H) == e(sum r^i W_i, tau * H)`,
+/// where `r` is derived from all openings.
+fn verify_aggregated(
+	public_parameters: &M1NoPrecomp<Bls12_381, BlstMSMEngine>,
+	openings: &[Opening],
+) -> Result<bool, Error> {
+	let g = public_parameters
+		.powers_of_g1
+		.first()
+		.ok_or(Error::InvalidDegree)?;
+	let (h, tau_h) = match public_parameters.powers_of_g2.get(..2) {
+		Some([h, tau_h]) => (h, tau_h),
+		_ => return Err(Error::InvalidDegree),
+	};
+
+	let mut transcript = Transcript::new(b"avail-batch-kzg");
+	for opening in openings {
+		transcript.append_message(b"row", &opening.cell.position.row.to_le_bytes());
+		transcript.append_message(b"col", &opening.cell.position.col.to_le_bytes());
+		transcript.append_message(b"commitment", opening.commitment);
+		transcript.append_message(b"content", &opening.cell.content);
+	}
+	let mut challenge = [0u8; 64];
+	transcript.challenge_bytes(b"batch-challenge", &mut challenge);
+	let r = Fr::from_le_bytes_mod_order(&challenge);
+
+	let mut r_i = Fr::from(1u64);
+	let mut lhs = G1::zero();
+	let mut rhs = G1::zero();
+	let mut values = Fr::zero();
+	for opening in openings {
+		lhs += opening.commitment_point * r_i;
+		lhs += opening.proof * (r_i * opening.point);
+		rhs += opening.proof * r_i;
+		values += r_i * opening.value;
+		r_i *= r;
+	}
+	lhs -= *g * values;
+
+	let pairing = Bls12_381::multi_pairing([lhs, -rhs], [*h, *tau_h]);
+	Ok(pairing.is_zero())
+}
+
 /// Generates domain points for a given size using arkworks primitives.
 pub fn domain_points(n: usize) -> Result<Vec<ArkScalar>, Error> {
 	let domain = GeneralEvaluationDomain::<ArkScalar>::new(n).ok_or(Error::InvalidDomain)?;
 	Ok(domain.elements().collect())
 }
 
-#[allow(clippy::type_complexity)]
+/// Multi-proof for a block of cells, along with evaluations of the block, row by row.
+pub type BlockMultiProof = ((Vec<[u8; 32]>, [u8; 48]), GCellBlock);
+
 /// Verifies a multi-proof for multiple cells with single proof using arkworks primitives.
+///
+/// Prefer [`verify_multi_proof_sync`], which does not need an executor.
 pub async fn verify_multi_proof(
 	pmp: &M1NoPrecomp<Bls12_381, BlstMSMEngine>,
-	proof: &[((Vec<[u8; 32]>, [u8; 48]), GCellBlock)],
+	proof: &[BlockMultiProof],
 	commitments: &[u8],
 	cols: usize, // Number of columns in the original grid
 ) -> Result<bool, Error> {
+	verify_multi_proof_sync(pmp, proof, commitments, cols)
+		.map(|verified| verified.into_iter().all(|block| block))
+}
+
+/// Verifies multi-proofs of cell blocks, in parallel if `std` is enabled.
+/// Returns verification result of each block, in the same order as `proof`.
+///
+/// # Arguments
+///
+/// * `pmp` - Public parameters the multi-proofs are generated with
+/// * `proof` - Evaluations, multi-proof and position of each cell block
+/// * `commitments` - Concatenated commitments of all rows
+/// * `cols` - Number of columns in the original grid
+pub fn verify_multi_proof_sync(
+	pmp: &M1NoPrecomp<Bls12_381, BlstMSMEngine>,
+	proof: &[BlockMultiProof],
+	commitments: &[u8],
+	cols: usize,
+) -> Result<Vec<bool>, Error> {
+	ensure!(
+		commitments.len() % COMMITMENT_SIZE == 0,
+		Error::InvalidCommitmentsLength
+	);
 	let points = domain_points(cols)?;
-	for ((eval, proof), cellblock) in proof.iter() {
-		let evals_flat = eval
-			.iter()
-			.map(ArkScalar::from_bytes)
-			.collect::<Result<Vec<_>, _>>()
-			.map_err(|_| Error::FailedToConvertEvalsToArkScalar)?;
-		let evals_grid = evals_flat
-			.chunks_exact((cellblock.end_x - cellblock.start_x) as usize)
-			.collect::<Vec<_>>();
-
-		let proofs = ArkProof::from_bytes(proof).map_err(|_| Error::FailedToParseProof)?;
-
-		let commits = commitments
-			.chunks_exact(48)
-			.skip(cellblock.start_y as usize)
-			.take((cellblock.end_y - cellblock.start_y) as usize)
-			.map(|c| ArkCommitment::from_bytes(c.try_into().unwrap()))
-			.collect::<Result<Vec<_>, _>>()
-			.map_err(|_| Error::FailedToExtractCommitments)?;
-
-		let verified = PolyMultiProofNoPrecomp::verify(
-			pmp,
-			&mut Transcript::new(b"avail-mp"),
-			&commits[..],
-			&points[(cellblock.start_x as usize)..(cellblock.end_x as usize)],
-			&evals_grid,
-			&proofs,
-		)
-		.map_err(|_| Error::FailedToVerifyProof)?;
-		if !verified {
-			return Ok(false);
+	let verify = |block| verify_block(pmp, &points, commitments, block);
+
+	#[cfg(feature = "std")]
+	let blocks = proof.par_iter();
+	#[cfg(not(feature = "std"))]
+	let blocks = proof.iter();
+
+	blocks.map(verify).collect()
+}
+
+fn verify_block(
+	pmp: &M1NoPrecomp<Bls12_381, BlstMSMEngine>,
+	points: &[ArkScalar],
+	commitments: &[u8],
+	((eval, proof), cellblock): &BlockMultiProof,
+) -> Result<bool, Error> {
+	let to_usize = |value: u32| usize::try_from(value).map_err(|_| Error::InvalidCellBlock);
+	let (start_x, end_x) = (to_usize(cellblock.start_x)?, to_usize(cellblock.end_x)?);
+	let (start_y, end_y) = (to_usize(cellblock.start_y)?, to_usize(cellblock.end_y)?);
+	ensure!(start_x < end_x && start_y < end_y, Error::InvalidCellBlock);
+	let (width, height) = (end_x - start_x, end_y - start_y);
+
+	let block_points = points
+		.get(start_x..end_x)
+		.ok_or(Error::InvalidPositionInDomain)?;
+	let expected = width.checked_mul(height).ok_or(Error::InvalidCellBlock)?;
+	ensure!(
+		eval.len() == expected,
+		Error::InvalidEvalsLength {
+			expected,
+			found: eval.len()
 		}
-	}
+	);
+
+	let evals_flat = eval
+		.iter()
+		.map(ArkScalar::from_bytes)
+		.collect::<Result<Vec<_>, _>>()
+		.map_err(|_| Error::FailedToConvertEvalsToArkScalar)?;
+	let evals_grid = evals_flat.chunks_exact(width).collect::<Vec<_>>();
+
+	let proofs = ArkProof::from_bytes(proof).map_err(|_| Error::FailedToParseProof)?;
+
+	let commits = start_y
+		.checked_mul(COMMITMENT_SIZE)
+		.zip(end_y.checked_mul(COMMITMENT_SIZE))
+		.and_then(|(start, end)| commitments.get(start..end))
+		.ok_or(Error::FailedToExtractCommitments)?
+		.chunks_exact(COMMITMENT_SIZE)
+		.map(|c| {
+			let c = c.try_into().expect("chunks are of commitment size .qed");
+			ArkCommitment::from_bytes(c)
+		})
+		.collect::<Result<Vec<_>, _>>()
+		.map_err(|_| Error::FailedToExtractCommitments)?;
 
-	Ok(true)
+	PolyMultiProofNoPrecomp::verify(
+		pmp,
+		&mut Transcript::new(b"avail-mp"),
+		&commits[..],
+		block_points,
+		&evals_grid,
+		&proofs,
+	)
+	.map_err(|_| Error::FailedToVerifyProof)
 }
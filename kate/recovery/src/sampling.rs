@@ -0,0 +1,375 @@
+//! Data availability sampling policy for light clients.
+//!
+//! Each column of the extended matrix is recoverable from any `rows` of its `extended_rows`
+//! cells, and each row from any `cols` of its `extended_cols` cells. Block data is therefore
+//! unrecoverable only if at least `(extended_rows - rows + 1) * (extended_cols - cols + 1)` cells
+//! are withheld. With columns extended only, that is just over `1 - 1 / row_factor` of a single
+//! column, so many more samples are needed than with a two-dimensional extension.
+//!
+//! [`confidence`] is the probability that uniformly chosen unique samples hit at least one cell of
+//! such a smallest withheld set. Withholding more cells only makes detection more likely, so it
+//! is a lower bound on the probability of detecting an unavailable block.
+use crate::matrix::{Dimensions, Position};
+use rand::{
+	seq::{index, SliceRandom},
+	Rng, SeedableRng,
+};
+use rand_chacha::ChaChaRng;
+use std::{collections::HashSet, convert::TryFrom};
+
+/// Strategy used to choose cells to sample.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Strategy {
+	/// Cells are chosen uniformly from the whole extended matrix.
+	Uniform,
+	/// Cells are spread over rows and columns, so no row or column is sampled
+	/// again before all of them have been sampled.
+	Stratified,
+}
+
+/// Seeded sampler of unique cells in the extended matrix.
+/// Cells returned by the sampler are never returned again.
+pub struct Sampler {
+	dimensions: Dimensions,
+	strategy: Strategy,
+	rng: ChaChaRng,
+	sampled: HashSet<Position>,
+}
+
+impl Sampler {
+	/// Creates sampler of cells in extended matrix of given dimensions.
+	/// Samplers with same seed choose same cells.
+	pub fn new(dimensions: Dimensions, strategy: Strategy, seed: [u8; 32]) -> Self {
+		Self {
+			dimensions,
+			strategy,
+			rng: ChaChaRng::from_seed(seed),
+			sampled: HashSet::new(),
+		}
+	}
+
+	/// Number of cells sampled so far.
+	pub fn sampled(&self) -> usize {
+		self.sampled.len()
+	}
+
+	/// Number of cells which are not sampled yet.
+	pub fn remaining(&self) -> usize {
+		self.total().saturating_sub(self.sampled.len())
+	}
+
+	fn total(&self) -> usize {
+		usize::try_from(self.dimensions.extended_size()).unwrap_or(usize::MAX)
+	}
+
+	/// Chooses `count` cells which were not sampled before,
+	/// or all remaining cells if there are less than `count` of them.
+	pub fn sample(&mut self, count: usize) -> Vec<Position> {
+		let count = count.min(self.remaining());
+		let mut positions = Vec::with_capacity(count);
+
+		if self.strategy == Strategy::Stratified {
+			self.sample_stratified(count, &mut positions);
+		}
+		self.sample_uniform(count, &mut positions);
+
+		positions
+	}
+
+	fn position(&self, index: usize) -> Position {
+		let cols = usize::from(self.dimensions.extended_cols());
+		let row = u32::try_from(index / cols).expect("index is within extended matrix .qed");
+		let col = u16::try_from(index % cols).expect("column is within extended matrix .qed");
+		Position { row, col }
+	}
+
+	fn sample_uniform(&mut self, count: usize, positions: &mut Vec<Position>) {
+		let total = self.total();
+		let missing = count.saturating_sub(positions.len());
+
+		// Rejection sampling gets slow as the matrix fills up, so pick from the remaining cells
+		if missing.saturating_mul(2) > self.remaining() {
+			let remaining = (0..total)
+				.map(|index| self.position(index))
+				.filter(|position| !self.sampled.contains(position))
+				.collect::<Vec<_>>();
+			for index in index::sample(&mut self.rng, remaining.len(), missing) {
+				self.sampled.insert(remaining[index]);
+				positions.push(remaining[index]);
+			}
+			return;
+		}
+
+		while positions.len() < count {
+			let index = self.rng.gen_range(0..total);
+			let position = self.position(index);
+			if self.sampled.insert(position) {
+				positions.push(position);
+			}
+		}
+	}
+
+	fn sample_stratified(&mut self, count: usize, positions: &mut Vec<Position>) {
+		let mut rows = self.dimensions.iter_extended_rows().collect::<Vec<_>>();
+		let mut cols = (0..self.dimensions.extended_cols()).collect::<Vec<_>>();
+		let round = rows.len().max(cols.len());
+
+		// Each round pairs shuffled rows with shuffled columns, hitting every row and column.
+		// Once rounds stop finding unsampled cells, the rest is sampled uniformly.
+		while positions.len() < count {
+			rows.shuffle(&mut self.rng);
+			cols.shuffle(&mut self.rng);
+			let found = positions.len();
+			for i in 0..round {
+				let position = Position::new(rows[i % rows.len()], cols[i % cols.len()]);
+				if positions.len() < count && self.sampled.insert(position) {
+					positions.push(position);
+				}
+			}
+			if positions.len() == found {
+				break;
+			}
+		}
+	}
+}
+
+/// Smallest number of withheld cells which makes block data unrecoverable.
+fn min_withheld(dimensions: Dimensions) -> f64 {
+	let rows = f64::from(dimensions.extended_rows()) - f64::from(dimensions.rows().get()) + 1.0;
+	let cols = f64::from(dimensions.extended_cols()) - f64::from(dimensions.cols().get()) + 1.0;
+	rows * cols
+}
+
+/// Probabilities that a smallest withheld set goes undetected after 1, 2, ... unique samples,
+/// chosen uniformly from the extended matrix.
+fn undetected(dimensions: Dimensions) -> impl Iterator<Item = f64> {
+	let total = f64::from(dimensions.extended_size());
+	let available = total - min_withheld(dimensions);
+	(0..dimensions.extended_size())
+		.map(f64::from)
+		.scan(1.0, move |undetected, sampled| {
+			*undetected *= ((available - sampled) / (total - sampled)).max(0.0);
+			Some(*undetected)
+		})
+}
+
+/// Probability, in range `[0, 1]`, that an unavailable block is detected
+/// after `samples` cells have been verified.
+pub fn confidence(dimensions: Dimensions, samples: u32) -> f64 {
+	let undetected = match samples.checked_sub(1) {
+		None => 1.0,
+		Some(nth) => usize::try_from(nth)
+			.ok()
+			.and_then(|nth| undetected(dimensions).nth(nth))
+			.unwrap_or(0.0),
+	};
+	1.0 - undetected
+}
+
+/// Minimum number of verified samples needed to reach `target` confidence.
+/// Returns `None` if the target is not in range `[0, 1]`.
+pub fn required_samples(dimensions: Dimensions, target: f64) -> Option<u32> {
+	if !(0.0..=1.0).contains(&target) {
+		return None;
+	}
+	if target <= 0.0 {
+		return Some(0);
+	}
+
+	undetected(dimensions)
+		.position(|undetected| 1.0 - undetected >= target)
+		.and_then(|samples| u32::try_from(samples.saturating_add(1)).ok())
+}
+
+/// Outcome of [`sample_until`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct SamplingOutcome {
+	/// Confidence reached by verified samples.
+	pub confidence: f64,
+	/// Positions of verified cells.
+	pub verified: Vec<Position>,
+	/// Positions of cells which failed to be fetched or verified.
+	pub failed: Vec<Position>,
+}
+
+impl SamplingOutcome {
+	/// Returns `true` if reached confidence is at least `target`.
+	pub fn is_confident(&self, target: f64) -> bool {
+		self.confidence >= target
+	}
+}
+
+/// Samples cells in rounds, until verified samples reach `target` confidence,
+/// `max_samples` cells are requested or all cells are sampled.
+/// Each round requests just enough cells to reach the target if all of them get verified.
+///
+/// # Arguments
+///
+/// * `sampler` - Sampler choosing cells to request
+/// * `target` - Target confidence, in range `[0, 1]`
+/// * `max_samples` - Maximum number of cells to request
+/// * `fetch_and_verify` - Fetches and verifies given cells, returning positions of verified ones
+pub fn sample_until<E>(
+	sampler: &mut Sampler,
+	target: f64,
+	max_samples: usize,
+	mut fetch_and_verify: impl FnMut(&[Position]) -> Result<Vec<Position>, E>,
+) -> Result<SamplingOutcome, E> {
+	let dimensions = sampler.dimensions;
+	let required = required_samples(dimensions, target)
+		.and_then(|samples| usize::try_from(samples).ok())
+		.unwrap_or(usize::MAX);
+
+	let mut outcome = SamplingOutcome {
+		confidence: 0.0,
+		verified: vec![],
+		failed: vec![],
+	};
+	let mut requested = 0usize;
+
+	while outcome.verified.len() < required && requested < max_samples {
+		let count = required
+			.saturating_sub(outcome.verified.len())
+			.min(max_samples.saturating_sub(requested));
+		let positions = sampler.sample(count);
+		if positions.is_empty() {
+			break;
+		}
+		requested = requested.saturating_add(positions.len());
+
+		let verified = fetch_and_verify(&positions)?
+			.into_iter()
+			.filter(|position| positions.contains(position))
+			.collect::<HashSet<_>>();
+		for position in positions {
+			if verified.contains(&position) {
+				outcome.verified.push(position);
+			} else {
+				outcome.failed.push(position);
+			}
+		}
+	}
+
+	let verified = u32::try_from(outcome.verified.len()).unwrap_or(u32::MAX);
+	outcome.confidence = confidence(dimensions, verified);
+	Ok(outcome)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::matrix::ExtensionConfig;
+	use std::convert::Infallible;
+
+	fn dimensions() -> Dimensions {
+		Dimensions::new(4, 8).unwrap()
+	}
+
+	#[test]
+	fn samplers_are_seeded() {
+		for strategy in [Strategy::Uniform, Strategy::Stratified] {
+			let mut first = Sampler::new(dimensions(), strategy, [1u8; 32]);
+			let mut second = Sampler::new(dimensions(), strategy, [1u8; 32]);
+			let mut third = Sampler::new(dimensions(), strategy, [2u8; 32]);
+			let samples = first.sample(10);
+			assert_eq!(samples, second.sample(10));
+			assert_ne!(samples, third.sample(10));
+		}
+	}
+
+	#[test]
+	fn samples_are_unique_until_exhausted() {
+		let dimensions = dimensions();
+		for strategy in [Strategy::Uniform, Strategy::Stratified] {
+			let mut sampler = Sampler::new(dimensions, strategy, [3u8; 32]);
+			let mut all = HashSet::new();
+			for count in [5, 20, 30, 10] {
+				for position in sampler.sample(count) {
+					assert!(dimensions.extended_contains(&position));
+					assert!(all.insert(position));
+				}
+			}
+			assert_eq!(all.len(), 64);
+			assert_eq!(sampler.remaining(), 0);
+			assert!(sampler.sample(1).is_empty());
+		}
+	}
+
+	#[test]
+	fn stratified_samples_cover_rows_and_columns() {
+		let dimensions = dimensions();
+		let mut sampler = Sampler::new(dimensions, Strategy::Stratified, [4u8; 32]);
+		let samples = sampler.sample(8);
+
+		let rows = samples.iter().map(|p| p.row).collect::<HashSet<_>>();
+		let cols = samples.iter().map(|p| p.col).collect::<HashSet<_>>();
+		assert_eq!(rows.len(), 8);
+		assert_eq!(cols.len(), 8);
+	}
+
+	#[test]
+	fn confidence_and_required_samples() {
+		// 2 extended rows and 4 columns, withholding both cells of a column is undetected
+		// by a single sample with probability 6/8.
+		let dimensions = Dimensions::new(1, 4).unwrap();
+		assert_eq!(confidence(dimensions, 0), 0.0);
+		assert_eq!(confidence(dimensions, 1), 0.25);
+		assert!(confidence(dimensions, 6) < 1.0);
+		assert_eq!(confidence(dimensions, 7), 1.0);
+		assert_eq!(confidence(dimensions, 100), 1.0);
+
+		assert_eq!(required_samples(dimensions, 0.0), Some(0));
+		assert_eq!(required_samples(dimensions, 0.25), Some(1));
+		assert_eq!(required_samples(dimensions, 1.0), Some(7));
+		assert_eq!(required_samples(dimensions, 1.5), None);
+	}
+
+	#[test]
+	fn columns_only_extension_needs_more_samples() {
+		// Withholding 257 of the 512 cells of a single column makes a 256x256 block unavailable.
+		let dimensions = Dimensions::new(256, 256).unwrap();
+		assert!(confidence(dimensions, 8) < 0.02);
+		assert_eq!(required_samples(dimensions, 0.99), Some(2326));
+
+		// With both dimensions extended, at least 257x257 cells must be withheld.
+		let extended = dimensions
+			.with_extension(ExtensionConfig::new(2, 2).unwrap())
+			.unwrap();
+		assert_eq!(required_samples(extended, 0.99), Some(16));
+	}
+
+	#[test]
+	fn sample_until_reaches_target() {
+		let mut sampler = Sampler::new(dimensions(), Strategy::Uniform, [5u8; 32]);
+		let mut rounds = 0;
+		// Every third cell fails verification, so more rounds are needed
+		let outcome = sample_until(&mut sampler, 0.9, 64, |positions| {
+			rounds += 1;
+			Ok::<_, Infallible>(
+				positions
+					.iter()
+					.filter(|position| (position.row + u32::from(position.col)) % 3 != 0)
+					.cloned()
+					.collect(),
+			)
+		})
+		.unwrap();
+
+		assert!(outcome.is_confident(0.9));
+		assert_eq!(outcome.verified.len(), 23);
+		assert!(!outcome.failed.is_empty());
+		assert!(rounds > 1);
+	}
+
+	#[test]
+	fn sample_until_stops_at_max_samples() {
+		let mut sampler = Sampler::new(dimensions(), Strategy::Stratified, [6u8; 32]);
+		let outcome =
+			sample_until(&mut sampler, 0.999, 6, |_| Ok::<_, Infallible>(vec![])).unwrap();
+
+		assert!(!outcome.is_confident(0.999));
+		assert_eq!(outcome.confidence, 0.0);
+		assert_eq!(outcome.failed.len(), 6);
+		assert_eq!(sampler.sampled(), 6);
+	}
+}
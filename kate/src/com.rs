@@ -11,34 +11,27 @@ use thiserror_no_std::Error;
 
 use avail_core::{
 	const_generic_asserts::{USizeGreaterOrEq, USizeSafeCastToU32, UsizeEven, UsizeNonZero},
-	constants::kate::{DATA_CHUNK_SIZE, EXTENSION_FACTOR},
+	constants::kate::DATA_CHUNK_SIZE,
 	data_lookup::Error as DataLookupError,
 	ensure, AppExtrinsic, AppId, BlockLengthColumns, BlockLengthRows, DataLookup,
 };
-use codec::Encode;
 use derive_more::Constructor;
 use nalgebra::base::DMatrix;
-use rand::Rng;
-use rand_chacha::{
-	rand_core::{Error as ChaChaError, SeedableRng},
-	ChaChaRng,
-};
+use rand_chacha::rand_core::Error as ChaChaError;
 use rayon::prelude::*;
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 use static_assertions::const_assert_eq;
 
 use crate::{
-	config::{
-		COL_EXTENSION, MAXIMUM_BLOCK_SIZE, MINIMUM_BLOCK_SIZE, PROOF_SIZE, ROW_EXTENSION,
-		SCALAR_SIZE,
-	},
+	config::{MAXIMUM_BLOCK_SIZE, MINIMUM_BLOCK_SIZE, PROOF_SIZE, SCALAR_SIZE},
+	layout::LayoutProfile,
 	metrics::Metrics,
 	padded_len_of_pad_iec_9797_1, BlockDimensions, Seed, TryFromBlockDimensionsError,
 };
 use kate_recovery::commons::{ArkEvaluationDomain, ArkPublicParams, ArkScalar};
 #[cfg(feature = "std")]
-use kate_recovery::matrix::Dimensions;
+use kate_recovery::matrix::{Dimensions, ExtensionConfig};
 #[cfg(feature = "std")]
 use poly_multiproof::ark_bls12_381::Bls12_381;
 #[cfg(feature = "std")]
@@ -95,6 +88,8 @@ pub enum Error {
 	ConversionFailed,
 	InvalidMaxRows,
 	InvalidMaxCols,
+	/// The public parameters do not support the degree of the grid polynomials
+	PublicParamsDegreeTooLow,
 }
 
 impl From<TryFromIntError> for Error {
@@ -121,12 +116,14 @@ impl PartialEq for Error {
 
 pub type XtsLayout = Vec<(AppId, u32)>;
 type FlatData = Vec<u8>;
-type DataChunk = [u8; DATA_CHUNK_SIZE];
+pub(crate) type DataChunk = [u8; DATA_CHUNK_SIZE];
 
 /// Helper which groups extrinsics data that share the same app_id.
 /// We assume the input extrinsics are already sorted by app_id, i.e. extrinsics with the same app_id are consecutive.
 /// This function does the same thing as group_by (unstable), just less general.
-fn app_extrinsics_group_by_app_id(extrinsics: &[AppExtrinsic]) -> Vec<(AppId, Vec<Vec<u8>>)> {
+pub(crate) fn app_extrinsics_group_by_app_id(
+	extrinsics: &[AppExtrinsic],
+) -> Vec<(AppId, Vec<Vec<u8>>)> {
 	extrinsics.iter().fold(vec![], |mut acc, e| {
 		match acc.last_mut() {
 			Some((app_id, data)) if e.app_id == *app_id => data.push(e.data.clone()),
@@ -136,78 +133,24 @@ fn app_extrinsics_group_by_app_id(extrinsics: &[AppExtrinsic]) -> Vec<(AppId, Ve
 	})
 }
 
+/// Lays `extrinsics` out using [`LayoutProfile::Com`], and flattens the grid into cells of
+/// `CHUNK_SIZE` bytes.
 pub fn flatten_and_pad_block<const CHUNK_SIZE: usize>(
 	max_rows: BlockLengthRows,
 	max_cols: BlockLengthColumns,
 	extrinsics: &[AppExtrinsic],
 	rng_seed: Seed,
 ) -> Result<(XtsLayout, FlatData, BlockDimensions), Error> {
-	// First, sort the extrinsics by their app_id
-	let mut extrinsics = extrinsics.to_vec();
-	extrinsics.sort_by(|a, b| a.app_id.cmp(&b.app_id));
-
-	// Pad data before determining exact block size
-	// Padding occurs both inside a single chunk and with additional chunk (if needed)
-	let (tx_layout, padded_chunks): (Vec<_>, Vec<_>) = app_extrinsics_group_by_app_id(&extrinsics)
-		.iter()
-		.map(|(app_id, opaques)| {
-			let data = opaques.encode();
-			let chunks = pad_iec_9797_1(data);
-			let chunks_len = u32::try_from(chunks.len()).map_err(|_| Error::BlockTooBig)?;
-			Ok(((*app_id, chunks_len), chunks))
-		})
-		.collect::<Result<Vec<_>, Error>>()?
+	let layout =
+		LayoutProfile::Com.layout::<CHUNK_SIZE>(extrinsics, max_rows, max_cols, rng_seed)?;
+	let padded_block = layout
+		.chunks
 		.into_iter()
-		.unzip();
-
-	let mut padded_block = padded_chunks
-		.into_iter()
-		.flat_map(|e| {
-			e.into_iter()
-				.flat_map(pad_to_chunk::<CHUNK_SIZE>)
-				.collect::<Vec<_>>()
-		})
+		.flat_map(pad_to_chunk::<CHUNK_SIZE>)
 		.collect::<Vec<_>>();
-	let padded_block_len: u32 = padded_block
-		.len()
-		.try_into()
-		.map_err(|_| Error::BlockTooBig)?;
-
-	// Determine the block size after padding
-	let block_dims = get_block_dimensions::<CHUNK_SIZE>(padded_block_len, max_rows, max_cols)?;
-
-	let block_dims_size = block_dims.size();
-	ensure!(padded_block.len() <= block_dims_size, Error::BlockTooBig);
-
-	let mut rng = ChaChaRng::from_seed(rng_seed);
-
-	// SAFETY: `padded_block.len() <= block_dims.size()` checked some lines above.
-	if cfg!(debug_assertions) {
-		let dims_sub_pad = block_dims_size
-			.checked_sub(padded_block.len())
-			.expect("`padded_block.len() <= block_dims.size() .qed");
-
-		// Static Assert that `CHUNK_SIZE` is non-zero.
-		#[allow(clippy::let_unit_value)]
-		let () = UsizeNonZero::<CHUNK_SIZE>::OK;
-		#[allow(clippy::arithmetic_side_effects)]
-		let rem = dims_sub_pad % CHUNK_SIZE;
-
-		assert_eq!(rem, 0);
-	}
-
-	#[allow(clippy::let_unit_value)]
-	let () = UsizeNonZero::<CHUNK_SIZE>::OK;
-	// SAFETY: `chunk_size` is non-zero, checked above.
-	#[allow(clippy::arithmetic_side_effects)]
-	let last = block_dims_size.saturating_sub(padded_block.len()) / CHUNK_SIZE;
+	debug_assert_eq!(padded_block.len(), layout.dims.size());
 
-	for _ in 0..last {
-		let rnd_values: [u8; SCALAR_SIZE - 1] = rng.gen();
-		padded_block.append(&mut pad_with_zeroes(rnd_values.to_vec(), CHUNK_SIZE));
-	}
-
-	Ok((tx_layout, padded_block, block_dims))
+	Ok((layout.xts_layout, padded_block, layout.dims))
 }
 
 pub fn get_block_dimensions<const CHUNK_SIZE: usize>(
@@ -273,6 +216,7 @@ pub fn get_block_dimensions<const CHUNK_SIZE: usize>(
 	BlockDimensions::new(rows, cols, chunk_size_u32).ok_or(Error::BlockTooBig)
 }
 
+#[cfg(test)]
 #[inline]
 fn pad_with_zeroes(mut chunk: Vec<u8>, len: usize) -> Vec<u8> {
 	chunk.resize(len, 0);
@@ -289,7 +233,7 @@ fn pad_to_chunk<const CHUNK_SIZE: usize>(chunk: DataChunk) -> Vec<u8> {
 	padded
 }
 
-fn pad_iec_9797_1(mut data: Vec<u8>) -> Vec<DataChunk> {
+pub(crate) fn pad_iec_9797_1(mut data: Vec<u8>) -> Vec<DataChunk> {
 	let data_len = u32::try_from(data.len()).unwrap_or(u32::MAX);
 	let padded_size = padded_len_of_pad_iec_9797_1(data_len);
 	data.resize(padded_size as usize, 0u8);
@@ -315,21 +259,24 @@ fn make_dims(bd: BlockDimensions) -> Result<Dimensions, Error> {
 /// Build extended data matrix, by columns.
 /// We are using dusk plonk for erasure coding,
 /// which is using roots of unity as evaluation domain for fft and ifft.
-/// This means that extension factor has to be multiple of 2,
+/// This means that extension factor has to be a power of 2,
 /// and that original data will be interleaved with erasure codes,
 /// instead of being in first k chunks of a column.
+/// Columns are extended by `extension.row_factor()`, and then rows are extended by
+/// `extension.col_factor()` if it is greater than one.
 ///
 /// `block` should be the raw data of a matrix, stored in row-major orientation.
 #[cfg(feature = "parallel")]
 pub fn par_extend_data_matrix<M: Metrics>(
 	block_dims: BlockDimensions,
+	extension: ExtensionConfig,
 	block: &[u8],
 	metrics: &M,
 ) -> Result<DMatrix<ArkScalar>, Error> {
 	let start = Instant::now();
 	let dims = make_dims(block_dims)?;
-	let (ext_rows, _): (usize, usize) = dims
-		.extend(ROW_EXTENSION, COL_EXTENSION)
+	let (ext_rows, ext_cols): (usize, usize) = dims
+		.extend(extension.row_factor(), extension.col_factor())
 		.ok_or(Error::InvalidDimensionExtension)?
 		.into();
 	let (rows, cols) = dims.into();
@@ -367,7 +314,26 @@ pub fn par_extend_data_matrix<M: Metrics>(
 		.collect::<Vec<_>>();
 	debug_assert_eq!(Some(ext_columns_wise.len()), cols.checked_mul(ext_rows));
 
-	let ext_matrix = DMatrix::from_iterator(ext_rows, cols, ext_columns_wise);
+	let mut ext_matrix = DMatrix::from_iterator(ext_rows, cols, ext_columns_wise);
+
+	if ext_cols != cols {
+		let row_eval_domain = ArkEvaluationDomain::new(cols).ok_or(Error::DomainSizeInvalid)?;
+		let extended_row_eval_domain =
+			ArkEvaluationDomain::new(ext_cols).ok_or(Error::InvalidDimensionExtension)?;
+
+		let ext_rows_wise = (0..ext_rows)
+			.into_par_iter()
+			.flat_map(|row_idx| {
+				let row = get_row(&ext_matrix, row_idx);
+				let coeffs = row_eval_domain.ifft(&row);
+				let extended_row = extended_row_eval_domain.fft(&coeffs);
+				debug_assert_eq!(extended_row.len(), ext_cols);
+				extended_row
+			})
+			.collect::<Vec<_>>();
+
+		ext_matrix = DMatrix::from_row_iterator(ext_rows, ext_cols, ext_rows_wise);
+	}
 
 	metrics.extended_block_time(start.elapsed());
 
@@ -382,15 +348,17 @@ pub fn build_proof<M: Metrics>(
 	metrics: &M,
 ) -> Result<Vec<u8>, Error> {
 	let dims = make_dims(block_dims)?;
-	let (ext_rows, ext_cols): (usize, usize) = dims
-		.extend(ROW_EXTENSION, COL_EXTENSION)
-		.ok_or(Error::InvalidDimensionExtension)?
-		.into();
-	let (_, cols): (usize, usize) = dims.into();
+	let (rows, cols): (usize, usize) = dims.into();
+	// Extension factors are implied by the shape of the extended matrix.
+	let (ext_rows, ext_cols) = ext_data_matrix.shape();
+	ensure!(
+		ext_rows.checked_rem(rows) == Some(0) && ext_cols.checked_rem(cols) == Some(0),
+		Error::InvalidDimensionExtension
+	);
 
 	const SPROOF_SIZE: usize = PROOF_SIZE + SCALAR_SIZE;
 
-	let row_eval_domain = ArkEvaluationDomain::new(cols).ok_or(Error::DomainSizeInvalid)?;
+	let row_eval_domain = ArkEvaluationDomain::new(ext_cols).ok_or(Error::DomainSizeInvalid)?;
 	let row_dom_x_pts = row_eval_domain.elements().collect::<Vec<_>>();
 
 	let mut result_bytes: Vec<u8> = vec![0u8; SPROOF_SIZE.saturating_mul(cells.len())];
@@ -406,10 +374,10 @@ pub fn build_proof<M: Metrics>(
 
 	let get_cell_row = |cell: &Cell| -> Result<(Vec<ArkScalar>, usize, usize), Error> {
 		let r_index = usize::try_from(cell.row.0)?;
-		if r_index >= ext_rows || cell.col >= block_dims.cols {
+		let c_index = usize::try_from(cell.col.0)?;
+		if r_index >= ext_rows || c_index >= ext_cols {
 			return Err(Error::IndexOutOfRange);
 		}
-		let c_index = usize::try_from(cell.col.0)?;
 
 		let get_ext_data_matrix =
 			|j: usize| ext_data_matrix[r_index.saturating_add(j.saturating_mul(ext_rows))];
@@ -441,14 +409,14 @@ pub fn build_proof<M: Metrics>(
 			return;
 		};
 
-		// # SAFETY: "`interpolate` function panics if row length is not equal to
-		// `block_dims.cols.next_power_of_two()`, which is true if `COL_EXTENSION` is 1.
-		const_assert_eq!(COL_EXTENSION.get(), 1);
 		// # SAFETY: `interpolate` function panics if the following debug assertion is not met,
 		// so it would simplify the location of that error.
-		debug_assert_eq!(row.len(), cols.next_power_of_two());
+		debug_assert_eq!(row.len(), ext_cols);
 
-		let poly = row_eval_domain.ifft(&row);
+		// Extended rows are evaluations of a polynomial of degree lower than `cols`, so the
+		// remaining coefficients are zero.
+		let mut poly = row_eval_domain.ifft(&row);
+		poly.truncate(cols);
 
 		let witness = match public_params.compute_witness_polynomial(poly, row_dom_x_pts[c_index]) {
 			Ok(w) => w,
@@ -526,17 +494,15 @@ pub fn par_build_commitments<const CHUNK_SIZE: usize, M: Metrics>(
 	let block_len = u32::try_from(block.len()).unwrap_or(u32::MAX);
 	metrics.block_dims_and_size(block_dims, block_len);
 
-	let ext_matrix = par_extend_data_matrix(block_dims, &block, metrics)?;
+	let ext_matrix =
+		par_extend_data_matrix(block_dims, ExtensionConfig::default(), &block, metrics)?;
 
 	let block_dims_cols = usize::try_from(block_dims.cols.0)?;
-	let block_dims_rows = usize::try_from(block_dims.rows.0)?;
-	let extended_rows = block_dims_rows
-		.checked_mul(EXTENSION_FACTOR as usize)
-		.ok_or(Error::BlockTooBig)?;
+	let extended_rows = ext_matrix.nrows();
 
 	metrics.preparation_block_time(start.elapsed());
 
-	let public_params = couscous::multiproof_params();
+	let public_params = couscous::public_params();
 	let row_eval_domain =
 		ArkEvaluationDomain::new(block_dims_cols).ok_or(Error::DomainSizeInvalid)?;
 
@@ -547,7 +513,7 @@ pub fn par_build_commitments<const CHUNK_SIZE: usize, M: Metrics>(
 		.into_par_iter()
 		.map(|row_idx| {
 			let ext_row = get_row(&ext_matrix, row_idx);
-			commit(&public_params, row_eval_domain, ext_row)
+			commit(public_params, row_eval_domain, ext_row)
 		})
 		.collect_into_vec(&mut commitments);
 
@@ -572,7 +538,7 @@ fn get_row(m: &DMatrix<ArkScalar>, row_idx: usize) -> Vec<ArkScalar> {
 #[cfg(feature = "std")]
 // Generate a commitment
 fn commit(
-	prover_key: &ArkPublicParams,
+	prover_key: &impl Committer<Bls12_381>,
 	domain: ArkEvaluationDomain,
 	row: Vec<ArkScalar>,
 ) -> Result<ArkCommitment, Error> {
@@ -679,6 +645,7 @@ mod tests {
 		prelude::*,
 	};
 	use rand::{prelude::IteratorRandom, Rng, SeedableRng};
+	use rand_chacha::ChaChaRng;
 	use std::{convert::TryInto, iter::repeat};
 	use test_case::test_case;
 
@@ -753,10 +720,59 @@ mod tests {
 			.chunks_exact(DATA_CHUNK_SIZE)
 			.flat_map(|chunk| pad_with_zeroes(chunk.to_vec(), chunk_size))
 			.collect::<Vec<u8>>();
-		let ext_matrix = par_extend_data_matrix(block_dims, &block, &IgnoreMetrics {}).unwrap();
+		let ext_matrix = par_extend_data_matrix(
+			block_dims,
+			ExtensionConfig::default(),
+			&block,
+			&IgnoreMetrics {},
+		)
+		.unwrap();
 		assert_eq!(ext_matrix, expected);
 	}
 
+	#[test]
+	fn test_extend_data_matrix_4x_reconstruct() {
+		let extension = ExtensionConfig::new(4, 1).unwrap();
+		let block_dims =
+			BlockDimensions::new(BlockLengthRows(2), BlockLengthColumns(4), TCHUNK).unwrap();
+		let chunk_size = usize::try_from(block_dims.chunk_size.get()).unwrap();
+		let block = (0..=247)
+			.collect::<Vec<u8>>()
+			.chunks_exact(DATA_CHUNK_SIZE)
+			.flat_map(|chunk| pad_with_zeroes(chunk.to_vec(), chunk_size))
+			.collect::<Vec<u8>>();
+		let scalars = block
+			.chunks_exact(chunk_size)
+			.map(to_bls_scalar)
+			.collect::<Result<Vec<_>, _>>()
+			.unwrap();
+		let data_matrix = DMatrix::from_row_iterator(2, 4, scalars);
+
+		let ext_matrix =
+			par_extend_data_matrix(block_dims, extension, &block, &IgnoreMetrics {}).unwrap();
+		assert_eq!(ext_matrix.shape(), (8, 4));
+
+		for col in 0..4 {
+			// Data is kept on every 4th row.
+			assert_eq!(ext_matrix[(0, col)], data_matrix[(0, col)]);
+			assert_eq!(ext_matrix[(4, col)], data_matrix[(1, col)]);
+
+			// Any 25% of the column is enough to reconstruct the data.
+			let cells = [3u32, 6]
+				.iter()
+				.map(|&row| DataCell {
+					position: Position::new(row, col as u16),
+					data: ext_matrix[(row as usize, col)].to_bytes().unwrap(),
+				})
+				.collect::<Vec<_>>();
+			let column = reconstruct_column(extension, 8, &cells).unwrap();
+			assert_eq!(
+				column,
+				data_matrix.column(col).iter().cloned().collect::<Vec<_>>()
+			);
+		}
+	}
+
 	#[test_case( 1..=29 => "0102030405060708090a0b0c0d0e0f101112131415161718191a1b1c1d0000" ; "chunk more than 3 values shorter")]
 	#[test_case( 1..=30 => "0102030405060708090a0b0c0d0e0f101112131415161718191a1b1c1d1e00" ; "Chunk 2 values shorter")]
 	#[test_case( 1..=31 => "0102030405060708090a0b0c0d0e0f101112131415161718191a1b1c1d1e1f00000000000000000000000000000000000000000000000000000000000000" ; "Chunk 1 value shorter")]
@@ -934,8 +950,9 @@ mod tests {
 			let position = Position { row: cell.row.0, col};
 			let cell = data::SingleCell { position,  content: proof.try_into().unwrap() };
 
-			let extended_dims = dims.try_into().unwrap();
-			let commitment = commitments::from_slice(&commitments).unwrap()[row];
+			let extended_dims: Dimensions = dims.try_into().unwrap();
+			let ext_rows = usize::try_from(extended_dims.extended_rows()).unwrap();
+			let commitment = commitments::from_slice(&commitments, ext_rows).unwrap()[row];
 			// let verification =  proof::verify(&public_params, extended_dims, &commitment,  &cell);
 			let verification =  proof::verify_v2(&public_params, extended_dims, &commitment,  &cell);
 			prop_assert!(verification.is_ok());
@@ -954,8 +971,9 @@ mod tests {
 		let index = DataLookup::from_id_and_len_iter(layout.into_iter()).unwrap();
 		// let dims_cols = usize::try_from(dims.cols.0).unwrap();
 		let public_params = couscous::multiproof_params();
-		let extended_dims = dims.try_into().unwrap();
-		let commitments = commitments::from_slice(&commitments).unwrap();
+		let extended_dims: Dimensions = dims.try_into().unwrap();
+		let ext_rows = usize::try_from(extended_dims.extended_rows()).unwrap();
+		let commitments = commitments::from_slice(&commitments, ext_rows).unwrap();
 		for xt in xts {
 			let rows = scalars_to_app_rows(xt.app_id, &index, extended_dims, &matrix);
 			let (_, missing) = commitments::verify_equality(&public_params, &commitments, rows.as_slice(), &index, extended_dims, xt.app_id).unwrap();
@@ -974,8 +992,9 @@ mod tests {
 		let index = DataLookup::from_id_and_len_iter(layout.into_iter()).unwrap();
 		// let dims_cols = usize::try_from(dims.cols.0).unwrap();
 		let public_params = couscous::multiproof_params();
-		let extended_dims =  dims.try_into().unwrap();
-		let commitments = commitments::from_slice(&commitments).unwrap();
+		let extended_dims: Dimensions = dims.try_into().unwrap();
+		let ext_rows = usize::try_from(extended_dims.extended_rows()).unwrap();
+		let commitments = commitments::from_slice(&commitments, ext_rows).unwrap();
 		for xt in xts {
 			let mut rows = scalars_to_app_rows(xt.app_id, &index, extended_dims, &matrix);
 			let app_row_index = rows.iter().position(Option::is_some).unwrap();
@@ -1011,6 +1030,58 @@ mod tests {
 		assert_eq!(commitments, expected_commitments);
 	}
 
+	#[test]
+	fn test_verify_batch() {
+		let xts = (0..4)
+			.map(|i| AppExtrinsic::new(AppId(i), vec![i as u8 + 1; 200]))
+			.collect::<Vec<_>>();
+		let metrics = IgnoreMetrics {};
+		let (_, commitments, dims, matrix) = par_build_commitments::<TCHUNK_SIZE, _>(
+			BlockLengthRows(64),
+			BlockLengthColumns(16),
+			&xts,
+			Seed::default(),
+			&metrics,
+		)
+		.unwrap();
+
+		let public_params = couscous::multiproof_params();
+		let cells = random_cells(dims.cols, dims.rows, 50);
+		let proofs = build_proof(&public_params, dims, &matrix, &cells, &metrics).unwrap();
+		let mut cells = cells
+			.iter()
+			.zip(proofs.chunks_exact(80))
+			.map(|(cell, content)| {
+				let col = cell.col.0.try_into().unwrap();
+				let position = Position::new(cell.row.0, col);
+				data::SingleCell::new(position, content.try_into().unwrap())
+			})
+			.collect::<Vec<_>>();
+
+		let extended_dims: Dimensions = dims.try_into().unwrap();
+		let ext_rows = usize::try_from(extended_dims.extended_rows()).unwrap();
+		let commitments = commitments::from_slice(&commitments, ext_rows).unwrap();
+		let verification =
+			proof::verify_batch(&public_params, extended_dims, &commitments, &cells).unwrap();
+		assert!(verification.is_valid());
+		assert_eq!(verification.verified, cells.len());
+
+		// Tamper data of one cell and swap proofs of two others
+		cells[1].content[48] ^= 1;
+		let proof_3 = cells[3].proof();
+		let proof_5 = cells[5].proof();
+		cells[3].content[..48].copy_from_slice(&proof_5);
+		cells[5].content[..48].copy_from_slice(&proof_3);
+		let mut expected = vec![cells[1].position, cells[3].position, cells[5].position];
+		expected.sort_by_key(|position| (position.row, position.col));
+
+		let verification =
+			proof::verify_batch(&public_params, extended_dims, &commitments, &cells).unwrap();
+		assert!(!verification.is_valid());
+		assert_eq!(verification.invalid, expected);
+		assert_eq!(verification.verified, cells.len() - 3);
+	}
+
 	#[test]
 	// newapi wip
 	fn test_reconstruct_app_extrinsics_with_app_id() -> Result<(), Error> {
@@ -1032,7 +1103,12 @@ get erasure coded to ensure redundancy."#;
 			&xts,
 			hash,
 		)?;
-		let matrix = par_extend_data_matrix(dims, &data[..], &IgnoreMetrics {})?;
+		let matrix = par_extend_data_matrix(
+			dims,
+			ExtensionConfig::default(),
+			&data[..],
+			&IgnoreMetrics {},
+		)?;
 
 		let cols_1 = sample_cells_from_matrix(&matrix, Some(&[0, 1, 2, 3]));
 
@@ -1071,7 +1147,12 @@ get erasure coded to ensure redundancy."#;
 			&xts,
 			hash,
 		)?;
-		let matrix = par_extend_data_matrix(dims, &data[..], &IgnoreMetrics {})?;
+		let matrix = par_extend_data_matrix(
+			dims,
+			ExtensionConfig::default(),
+			&data[..],
+			&IgnoreMetrics {},
+		)?;
 		let dimensions: Dimensions = dims.try_into()?;
 
 		let index = DataLookup::from_id_and_len_iter(layout.into_iter()).unwrap();
@@ -1113,7 +1194,12 @@ Let's see how this gets encoded and then reconstructed by sampling only some dat
 			hash,
 		)?;
 
-		let matrix = par_extend_data_matrix(dims, &data[..], &IgnoreMetrics {})?;
+		let matrix = par_extend_data_matrix(
+			dims,
+			ExtensionConfig::default(),
+			&data[..],
+			&IgnoreMetrics {},
+		)?;
 
 		let cols = sample_cells_from_matrix(&matrix, None);
 
@@ -1145,7 +1231,12 @@ Let's see how this gets encoded and then reconstructed by sampling only some dat
 			hash,
 		)?;
 
-		let matrix = par_extend_data_matrix(dims, &data[..], &IgnoreMetrics {})?;
+		let matrix = par_extend_data_matrix(
+			dims,
+			ExtensionConfig::default(),
+			&data[..],
+			&IgnoreMetrics {},
+		)?;
 
 		let cols = sample_cells_from_matrix(&matrix, None);
 		let extended_dims = dims.try_into().unwrap();
@@ -8,38 +8,65 @@ use poly_multiproof::{
 };
 use sp_std::vec::Vec;
 
+#[cfg(feature = "precomputed-srs")]
+use crate::fixed_base::FixedBaseTable;
+#[cfg(feature = "std")]
+use avail_core::ensure;
+#[cfg(feature = "std")]
+use core::iter::{once, successors};
+#[cfg(feature = "std")]
+use kate_recovery::commons::ArkScalar;
+#[cfg(feature = "std")]
+use once_cell::sync::OnceCell;
+#[cfg(feature = "std")]
+use poly_multiproof::{
+	ark_bls12_381::{Bls12_381, Fr},
+	ark_ff::{One, PrimeField, Zero},
+	merlin::Transcript,
+	method1::Proof,
+	traits::{Committer, KZGProof},
+	Commitment, Error as MultiproofError, Pairing,
+};
+#[cfg(feature = "std")]
+use serde::Deserialize;
+#[cfg(feature = "std")]
+use thiserror_no_std::Error;
+
 // Loads the pre-generated trusted g1 & g2 from the file
 fn load_trusted_g1_g2() -> (Vec<G1>, Vec<G2>) {
 	// For degree 1024, we include 513 G2 points.
 	// The rationale is that in multiproof constructions, we never need more than half the degree in G2 points.
 	// Creating a multiproof grid with width equal to the original data grid doesn't make sense.
 	let contents = include_str!("g1_g2_1024.txt");
+	parse_text(contents).expect("Embedded trusted setup is well formed .qed")
+}
+
+/// Parses a trusted setup in the format of the embedded one: the number of g1 and g2 powers on
+/// the first two lines, followed by one hex encoded compressed point per line.
+fn parse_text(contents: &str) -> Option<(Vec<G1>, Vec<G2>)> {
 	let mut lines = contents.lines();
-	let g1_len: usize = lines.next().unwrap().parse().unwrap();
-	let g2_len: usize = lines.next().unwrap().parse().unwrap();
+	let g1_len: usize = lines.next()?.trim().parse().ok()?;
+	let g2_len: usize = lines.next()?.trim().parse().ok()?;
 
-	let g1_bytes: Vec<[u8; 48]> = lines
+	let g1 = lines
 		.by_ref()
 		.take(g1_len)
-		.map(|line| hex::decode(line).unwrap().try_into().unwrap())
-		.collect();
-
-	let g2_bytes: Vec<[u8; 96]> = lines
+		.map(decode_point::<G1, 48>)
+		.collect::<Option<Vec<_>>>()?;
+	let g2 = lines
 		.take(g2_len)
-		.map(|line| hex::decode(line).unwrap().try_into().unwrap())
-		.collect();
+		.map(decode_point::<G2, 96>)
+		.collect::<Option<Vec<_>>>()?;
 
-	let g1: Vec<G1> = g1_bytes
-		.iter()
-		.map(|bytes| G1::deserialize_compressed(&bytes[..]).unwrap())
-		.collect();
-
-	let g2: Vec<G2> = g2_bytes
-		.iter()
-		.map(|bytes| G2::deserialize_compressed(&bytes[..]).unwrap())
-		.collect();
+	(g1.len() == g1_len && g2.len() == g2_len).then_some((g1, g2))
+}
 
-	(g1, g2)
+/// Decodes a hex encoded, optionally `0x` prefixed, compressed point of `N` bytes.
+fn decode_point<P: CanonicalDeserialize, const N: usize>(line: &str) -> Option<P> {
+	let line = line.trim();
+	let line = line.strip_prefix("0x").unwrap_or(line);
+	let bytes: [u8; N] = hex::decode(line).ok()?.try_into().ok()?;
+	P::deserialize_compressed(&bytes[..]).ok()
 }
 
 ///  Construct public parameters from pre-generated points for degree upto 1024
@@ -48,10 +75,264 @@ pub fn multiproof_params() -> ArkPublicParams {
 	ArkPublicParams::new_from_powers(&g1, &g2)
 }
 
+/// Encodings of a trusted setup accepted by [`load_srs`].
+#[cfg(feature = "std")]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SrsFormat {
+	/// Format of the embedded setup: the number of g1 and g2 powers on the first two lines,
+	/// followed by one hex encoded compressed point per line.
+	Text,
+	/// JSON output of the Ethereum KZG ceremony. Either the full transcript, whose sub-ceremony
+	/// with the most g1 powers is loaded, or the `g1_monomial` and `g2_monomial` lists of the
+	/// consensus specs trusted setup.
+	EthereumJson,
+}
+
+#[cfg(feature = "std")]
+#[derive(Error, Debug)]
+pub enum SrsError {
+	#[error("Trusted setup cannot be read: {0}")]
+	Io(#[from] std::io::Error),
+	#[error("Trusted setup JSON is malformed: {0}")]
+	Json(#[from] serde_json::Error),
+	#[error("Trusted setup contains malformed or invalid points")]
+	InvalidPoints,
+	#[error("Trusted setup needs at least two non-zero powers of g1 and g2")]
+	NotEnoughPowers,
+	#[error("Trusted setup is generated with tau equal to one")]
+	DegenerateTau,
+	#[error("Powers of g1 are not successive powers of tau")]
+	InconsistentG1Powers,
+	#[error("Powers of g2 do not match the powers of g1")]
+	InconsistentG2Powers,
+	#[error("Multiproof error: {0}")]
+	Multiproof(#[from] MultiproofError),
+}
+
+#[cfg(feature = "std")]
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum EthereumSetup {
+	Transcript {
+		transcripts: Vec<EthereumTranscript>,
+	},
+	Monomial {
+		g1_monomial: Vec<String>,
+		g2_monomial: Vec<String>,
+	},
+}
+
+#[cfg(feature = "std")]
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct EthereumTranscript {
+	powers_of_tau: EthereumPowersOfTau,
+}
+
+#[cfg(feature = "std")]
+#[derive(Deserialize)]
+struct EthereumPowersOfTau {
+	#[serde(rename = "G1Powers")]
+	g1_powers: Vec<String>,
+	#[serde(rename = "G2Powers")]
+	g2_powers: Vec<String>,
+}
+
+#[cfg(feature = "std")]
+fn parse_ethereum_json(bytes: &[u8]) -> Result<(Vec<G1>, Vec<G2>), SrsError> {
+	let (g1, g2) = match serde_json::from_slice::<EthereumSetup>(bytes)? {
+		EthereumSetup::Transcript { transcripts } => {
+			let powers = transcripts
+				.into_iter()
+				.map(|transcript| transcript.powers_of_tau)
+				.max_by_key(|powers| powers.g1_powers.len())
+				.ok_or(SrsError::NotEnoughPowers)?;
+			(powers.g1_powers, powers.g2_powers)
+		},
+		EthereumSetup::Monomial {
+			g1_monomial,
+			g2_monomial,
+		} => (g1_monomial, g2_monomial),
+	};
+
+	let g1 = g1
+		.iter()
+		.map(|point| decode_point::<G1, 48>(point))
+		.collect::<Option<Vec<_>>>();
+	let g2 = g2
+		.iter()
+		.map(|point| decode_point::<G2, 96>(point))
+		.collect::<Option<Vec<_>>>();
+	g1.zip(g2).ok_or(SrsError::InvalidPoints)
+}
+
+/// Loads a trusted setup of any degree from `bytes` encoded as `format`.
+///
+/// The powers are checked to be consistent with each other before they are returned, see
+/// [`verify_powers`].
+#[cfg(feature = "std")]
+pub fn load_srs(bytes: &[u8], format: SrsFormat) -> Result<ArkPublicParams, SrsError> {
+	let (g1, g2) = match format {
+		SrsFormat::Text => core::str::from_utf8(bytes)
+			.ok()
+			.and_then(parse_text)
+			.ok_or(SrsError::InvalidPoints)?,
+		SrsFormat::EthereumJson => parse_ethereum_json(bytes)?,
+	};
+	let params = ArkPublicParams::new_from_powers(&g1, &g2);
+
+	// The challenge depends on every point of the setup, so it cannot be chosen to cancel
+	// inconsistent powers out.
+	let mut transcript = Transcript::new(b"avail-srs");
+	transcript.append_message(b"srs", bytes);
+	let mut challenge = [0u8; 64];
+	transcript.challenge_bytes(b"srs-challenge", &mut challenge);
+	verify_powers(&params, Fr::from_le_bytes_mod_order(&challenge))?;
+
+	Ok(params)
+}
+
+/// Loads a trusted setup from the file at `path`, see [`load_srs`].
+#[cfg(feature = "std")]
+pub fn load_srs_file(
+	path: impl AsRef<std::path::Path>,
+	format: SrsFormat,
+) -> Result<ArkPublicParams, SrsError> {
+	load_srs(&std::fs::read(path)?, format)
+}
+
+/// Checks that the powers of g1 and g2 of `params` are successive powers of the same `τ`, i.e.
+/// `e(g1^{τ^i}, g2) == e(g1^{τ^{i-1}}, g2^τ)` and `e(g1^{τ^j}, g2) == e(g1, g2^{τ^j})`.
+///
+/// Each set of equations is combined into a single pairing check using powers of `challenge`,
+/// which must be unpredictable by whoever generated the setup.
+#[cfg(feature = "std")]
+#[allow(clippy::arithmetic_side_effects)]
+pub fn verify_powers(params: &ArkPublicParams, challenge: Fr) -> Result<(), SrsError> {
+	let (g1, g2) = (&params.powers_of_g1, &params.powers_of_g2);
+	let (Some(&g1_0), Some(&[g2_0, g2_tau])) = (g1.first(), g2.get(..2)) else {
+		return Err(SrsError::NotEnoughPowers);
+	};
+	ensure!(
+		g1.len() >= 2 && !G1::from(g1_0).is_zero() && !G2::from(g2_0).is_zero(),
+		SrsError::NotEnoughPowers
+	);
+	ensure!(g2.len() <= g1.len(), SrsError::InconsistentG2Powers);
+	// `τ == 1` passes the checks below, as every power is the generator itself.
+	ensure!(
+		g1.get(1) != Some(&g1_0) && g2_tau != g2_0,
+		SrsError::DegenerateTau
+	);
+
+	let challenges = successors(Some(Fr::one()), |r| Some(*r * challenge))
+		.take(g1.len())
+		.collect::<Vec<_>>();
+	let init = challenges.get(..g1.len() - 1).unwrap_or_default();
+
+	// `sum(r^i * g1^{τ^(i+1)})` must be `τ` times `sum(r^i * g1^{τ^i})`.
+	let shifted = once(Fr::zero())
+		.chain(init.iter().copied())
+		.collect::<Vec<_>>();
+	let Commitment(shifted) = params.commit(&shifted)?;
+	let Commitment(unshifted) = params.commit(init)?;
+	ensure!(
+		Bls12_381::multi_pairing([shifted, -unshifted], [g2_0, g2_tau]).is_zero(),
+		SrsError::InconsistentG1Powers
+	);
+
+	// `sum(r^j * g1^{τ^j})` must match `sum(r^j * g2^{τ^j})`.
+	let challenges = challenges.get(..g2.len()).unwrap_or_default();
+	let Commitment(g1_sum) = params.commit(challenges)?;
+	let g2_sum = g2
+		.iter()
+		.zip(challenges)
+		.fold(G2::zero(), |acc, (point, r)| acc + *point * r);
+	ensure!(
+		Bls12_381::multi_pairing([g1_sum, -g1_0], [G2::from(g2_0), g2_sum]).is_zero(),
+		SrsError::InconsistentG2Powers
+	);
+
+	Ok(())
+}
+
+#[cfg(feature = "std")]
+static PUBLIC_PARAMS: OnceCell<PublicParams> = OnceCell::new();
+
+/// Public parameters used by default, e.g. by [`crate::com::par_build_commitments`].
+///
+/// These are the ones set by [`set_public_params`], or the embedded trusted setup, parsed on
+/// first use only.
+#[cfg(feature = "std")]
+pub fn public_params() -> &'static PublicParams {
+	PUBLIC_PARAMS.get_or_init(|| PublicParams::new(multiproof_params()))
+}
+
+/// Sets the public parameters returned by [`public_params`] for the rest of the process, e.g. a
+/// setup loaded by [`load_srs_file`] and checked by [`verify_powers`].
+///
+/// Returns `params` back if the public parameters were already set or used.
+#[cfg(feature = "std")]
+pub fn set_public_params(params: PublicParams) -> Result<(), PublicParams> {
+	PUBLIC_PARAMS.set(params)
+}
+
+/// Public parameters used to commit to the data grid.
+///
+/// With the `precomputed-srs` feature, the powers of `g1` are expanded into a
+/// [`FixedBaseTable`] (32 points per power), and commitments are computed over it.
+#[cfg(feature = "std")]
+pub struct PublicParams {
+	params: ArkPublicParams,
+	#[cfg(feature = "precomputed-srs")]
+	table: FixedBaseTable,
+}
+
+#[cfg(feature = "std")]
+impl PublicParams {
+	pub fn new(params: ArkPublicParams) -> Self {
+		Self {
+			#[cfg(feature = "precomputed-srs")]
+			table: FixedBaseTable::new(&params.powers_of_g1),
+			params,
+		}
+	}
+
+	/// Parameters to generate and verify proofs with.
+	pub fn multiproof_params(&self) -> &ArkPublicParams {
+		&self.params
+	}
+
+	/// Opens `poly` at `point`, committing to the witness polynomial like `commit` does.
+	pub fn open(
+		&self,
+		poly: Vec<ArkScalar>,
+		point: ArkScalar,
+	) -> Result<Proof<Bls12_381>, MultiproofError> {
+		let witness = KZGProof::compute_witness_polynomial(&self.params, poly, point)?;
+		let Commitment(witness) = self.commit(witness)?;
+		Ok(Proof(witness))
+	}
+}
+
+#[cfg(feature = "std")]
+impl Committer<Bls12_381> for PublicParams {
+	fn commit(
+		&self,
+		poly: impl AsRef<[ArkScalar]>,
+	) -> Result<Commitment<Bls12_381>, MultiproofError> {
+		#[cfg(feature = "precomputed-srs")]
+		if let Some(commitment) = self.table.msm(poly.as_ref()) {
+			return Ok(Commitment(commitment.into()));
+		}
+		self.params.commit(poly)
+	}
+}
+
 #[cfg(test)]
 mod tests {
 	use super::*;
 	use crate::*;
+	use kate_recovery::testnet;
 	use pmp::{
 		ark_poly::{
 			univariate::DensePolynomial, DenseUVPolynomial, EvaluationDomain,
@@ -59,8 +340,14 @@ mod tests {
 		},
 		traits::KZGProof,
 	};
-	use poly_multiproof::{ark_bls12_381::Fr, msm::blst::BlstMSMEngine, traits::Committer};
+	use poly_multiproof::{
+		ark_bls12_381::{Fr, G1Affine, G2Affine},
+		ark_serialize::CanonicalSerialize,
+		msm::blst::BlstMSMEngine,
+		traits::{AsBytes, Committer},
+	};
 	use rand::thread_rng;
+	use serde_json::json;
 
 	#[test]
 	fn test_testnet_params() {
@@ -86,4 +373,120 @@ mod tests {
 
 		assert!(verify1);
 	}
+
+	#[test]
+	fn public_params_match_multiproof_params() {
+		let public_params = public_params();
+		assert!(core::ptr::eq(public_params, super::public_params()));
+		let params = public_params.multiproof_params();
+
+		let poly = DensePolynomial::<Fr>::rand(255, &mut thread_rng()).coeffs;
+		let commitment = public_params.commit(&poly).unwrap();
+		let expected = params.commit(&poly).unwrap();
+		assert_eq!(commitment.to_bytes().unwrap(), expected.to_bytes().unwrap());
+
+		let point = Fr::from(7u64);
+		let proof = public_params.open(poly.clone(), point).unwrap();
+		let witness = params.compute_witness_polynomial(poly, point).unwrap();
+		let expected = params.open(witness).unwrap();
+		assert_eq!(proof.to_bytes().unwrap(), expected.to_bytes().unwrap());
+	}
+
+	fn to_hex<P: CanonicalSerialize>(point: &P) -> String {
+		let mut bytes = Vec::new();
+		point.serialize_compressed(&mut bytes).unwrap();
+		format!("0x{}", hex::encode(bytes))
+	}
+
+	fn params_from(g1: &[G1Affine], g2: &[G2Affine]) -> ArkPublicParams {
+		let g1 = g1.iter().copied().map(G1::from).collect::<Vec<_>>();
+		let g2 = g2.iter().copied().map(G2::from).collect::<Vec<_>>();
+		ArkPublicParams::new_from_powers(&g1, &g2)
+	}
+
+	#[test]
+	fn load_embedded_srs_as_text() {
+		let params = load_srs(include_bytes!("g1_g2_1024.txt"), SrsFormat::Text).unwrap();
+		let embedded = multiproof_params();
+		assert_eq!(params.powers_of_g1, embedded.powers_of_g1);
+		assert_eq!(params.powers_of_g2, embedded.powers_of_g2);
+
+		let truncated = "4\n2\n".to_owned() + &to_hex(&embedded.powers_of_g1[0]);
+		assert!(matches!(
+			load_srs(truncated.as_bytes(), SrsFormat::Text),
+			Err(SrsError::InvalidPoints)
+		));
+	}
+
+	#[test]
+	fn load_ethereum_json_above_degree_1024() {
+		let srs = testnet::multiproof_params(2047, 64);
+		let g1 = srs.powers_of_g1.iter().map(to_hex).collect::<Vec<_>>();
+		let g2 = srs.powers_of_g2.iter().map(to_hex).collect::<Vec<_>>();
+
+		let transcript = json!({
+			"transcripts": [
+				{
+					"numG1Powers": 4,
+					"numG2Powers": 2,
+					"powersOfTau": { "G1Powers": g1[..4], "G2Powers": g2[..2] },
+				},
+				{
+					"numG1Powers": g1.len(),
+					"numG2Powers": g2.len(),
+					"powersOfTau": { "G1Powers": g1, "G2Powers": g2 },
+				},
+			],
+		});
+		let monomial = json!({ "g1_monomial": g1, "g1_lagrange": [], "g2_monomial": g2 });
+
+		for setup in [transcript, monomial] {
+			let bytes = setup.to_string().into_bytes();
+			let params = load_srs(&bytes, SrsFormat::EthereumJson).unwrap();
+			assert_eq!(params.powers_of_g1.len(), 2048);
+			assert_eq!(params.powers_of_g1, srs.powers_of_g1);
+			assert_eq!(params.powers_of_g2, srs.powers_of_g2);
+		}
+	}
+
+	#[test]
+	fn verify_powers_rejects_inconsistent_setups() {
+		let srs = testnet::multiproof_params(63, 8);
+		let (g1, g2) = (&srs.powers_of_g1, &srs.powers_of_g2);
+		let challenge = Fr::from(5u64);
+		assert!(verify_powers(&srs, challenge).is_ok());
+
+		let mut swapped = g1.clone();
+		swapped.swap(10, 11);
+		assert!(matches!(
+			verify_powers(&params_from(&swapped, g2), challenge),
+			Err(SrsError::InconsistentG1Powers)
+		));
+
+		let mut replaced = g2.clone();
+		replaced[3] = replaced[4];
+		assert!(matches!(
+			verify_powers(&params_from(g1, &replaced), challenge),
+			Err(SrsError::InconsistentG2Powers)
+		));
+
+		assert!(matches!(
+			verify_powers(&params_from(&g1[..1], g2), challenge),
+			Err(SrsError::NotEnoughPowers)
+		));
+
+		// Powers of `τ == 1` are consistent, but they are all the generators.
+		let (g1_ones, g2_ones) = (vec![g1[0]; g1.len()], vec![g2[0]; g2.len()]);
+		let degenerate = params_from(&g1_ones, &g2_ones);
+		assert!(matches!(
+			verify_powers(&degenerate, challenge),
+			Err(SrsError::DegenerateTau)
+		));
+		let mut degenerate_g2 = g2.clone();
+		degenerate_g2[1] = g2[0];
+		assert!(matches!(
+			verify_powers(&params_from(g1, &degenerate_g2), challenge),
+			Err(SrsError::DegenerateTau)
+		));
+	}
 }
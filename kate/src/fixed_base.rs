@@ -0,0 +1,126 @@
+//! Multi-scalar multiplication over fixed bases, using precomputed multiples of every base.
+
+use crate::pmp::{
+	ark_bls12_381::{Bls12_381, Fr},
+	ark_ff::{BigInteger, PrimeField, Zero},
+	Pairing,
+};
+use ark_ec::{CurveGroup, Group};
+use rayon::prelude::*;
+
+type G1 = <Bls12_381 as Pairing>::G1;
+type G1Affine = <Bls12_381 as Pairing>::G1Affine;
+
+/// Bits of each digit of a scalar.
+const DIGIT_BITS: usize = 8;
+/// Digits of a scalar, as `Fr` fits into 32 bytes.
+const DIGITS: usize = 32;
+/// Buckets of the non-zero digits.
+const BUCKETS: usize = (1 << DIGIT_BITS) - 1;
+
+/// Multiples `2^(8 * i) * base`, for every digit `i` of a scalar, of a list of bases.
+///
+/// An MSM over the bases becomes a single pass of the bucket method over the multiples, as
+/// every byte of a scalar selects the bucket its multiple is added to. No doublings are
+/// needed, at the cost of keeping `32` points per base.
+pub struct FixedBaseTable {
+	multiples: Vec<G1Affine>,
+}
+
+impl FixedBaseTable {
+	#[allow(clippy::arithmetic_side_effects)]
+	pub fn new(bases: &[G1Affine]) -> Self {
+		let multiples = bases
+			.par_iter()
+			.flat_map_iter(|base| {
+				let mut multiple = G1::from(*base);
+				(0..DIGITS).map(move |_| {
+					let current = multiple;
+					for _ in 0..DIGIT_BITS {
+						multiple.double_in_place();
+					}
+					current
+				})
+			})
+			.collect::<Vec<_>>();
+
+		// A single field inversion for all the multiples, instead of one per multiple.
+		let multiples = G1::normalize_batch(&multiples);
+		Self { multiples }
+	}
+
+	/// Number of bases.
+	pub fn len(&self) -> usize {
+		self.multiples.len() / DIGITS
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.multiples.is_empty()
+	}
+
+	/// Computes `scalars[0] * bases[0] + ... + scalars[n - 1] * bases[n - 1]`.
+	/// Returns `None` if there are more scalars than bases.
+	#[allow(clippy::arithmetic_side_effects)]
+	pub fn msm(&self, scalars: &[Fr]) -> Option<G1> {
+		if scalars.len() > self.len() {
+			return None;
+		}
+
+		let mut buckets = vec![G1::zero(); BUCKETS];
+		let bases = self.multiples.chunks_exact(DIGITS);
+		for (scalar, multiples) in scalars.iter().zip(bases) {
+			let digits = scalar.into_bigint().to_bytes_le();
+			for (&digit, multiple) in digits.iter().zip(multiples) {
+				if let Some(bucket) = usize::from(digit)
+					.checked_sub(1)
+					.and_then(|idx| buckets.get_mut(idx))
+				{
+					*bucket += multiple;
+				}
+			}
+		}
+
+		// `sum(digit * bucket)`, as the running sum of bucket `d` is added `d` times.
+		let mut running = G1::zero();
+		let mut sum = G1::zero();
+		for bucket in buckets.into_iter().rev() {
+			running += bucket;
+			sum += running;
+		}
+		Some(sum)
+	}
+}
+
+#[cfg(test)]
+#[allow(clippy::arithmetic_side_effects)]
+mod tests {
+	use super::*;
+	use crate::couscous;
+	use rand::{Rng, SeedableRng};
+	use rand_chacha::ChaChaRng;
+
+	#[test]
+	fn msm_matches_naive_sum() {
+		let bases = couscous::multiproof_params().powers_of_g1[..64].to_vec();
+		let table = FixedBaseTable::new(&bases);
+		assert_eq!(table.len(), bases.len());
+
+		let mut rng = ChaChaRng::from_seed([3u8; 32]);
+		for len in [0, 1, 17, 64] {
+			let mut scalars = (0..len)
+				.map(|_| Fr::from_le_bytes_mod_order(&rng.gen::<[u8; 32]>()))
+				.collect::<Vec<_>>();
+			if let Some(first) = scalars.first_mut() {
+				*first = -Fr::from(1u64);
+			}
+
+			let expected = scalars
+				.iter()
+				.zip(bases.iter())
+				.fold(G1::zero(), |acc, (scalar, base)| acc + *base * scalar);
+			assert_eq!(table.msm(&scalars), Some(expected));
+		}
+
+		assert_eq!(table.msm(&vec![Fr::from(1u64); 65]), None);
+	}
+}
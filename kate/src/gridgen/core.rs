@@ -7,28 +7,28 @@ use crate::pmp::{
 	Pairing,
 };
 use avail_core::{
-	app_extrinsic::AppExtrinsic, constants::kate::DATA_CHUNK_SIZE, ensure, AppId, DataLookup,
+	app_extrinsic::AppExtrinsic, constants::kate::DATA_CHUNK_SIZE, ensure, AppId,
+	BlockLengthColumns, BlockLengthRows, DataLookup,
 };
-use codec::Encode;
 use core::{
 	cmp::{max, min},
-	iter,
-	num::NonZeroU16,
+	num::{NonZeroU16, NonZeroUsize},
 };
-use kate_recovery::matrix::Dimensions;
+use kate_recovery::matrix::{Dimensions, ExtensionConfig};
 use nalgebra::base::DMatrix;
 use poly_multiproof::{
+	ark_ff::Zero,
 	method1::Proof,
 	traits::{KZGProof, PolyMultiProofNoPrecomp},
 };
-use rand::Rng;
-use rand_chacha::{rand_core::SeedableRng, ChaChaRng};
 use static_assertions::const_assert;
-use std::collections::BTreeMap;
+use std::{collections::VecDeque, sync::Arc};
 use thiserror_no_std::Error;
 
 use crate::{
 	com::{Cell, Error},
+	couscous::PublicParams,
+	layout::{GridLayout, LayoutProfile},
 	ArkScalar, Seed,
 };
 
@@ -65,7 +65,8 @@ pub enum AppRowError {
 }
 
 impl EvaluationGrid {
-	/// From the app extrinsics, create a data grid of Scalars
+	/// From the app extrinsics, create a data grid of Scalars laid out by
+	/// [`LayoutProfile::Gridgen`].
 	pub fn from_extrinsics(
 		extrinsics: Vec<AppExtrinsic>,
 		min_width: usize,
@@ -73,49 +74,33 @@ impl EvaluationGrid {
 		max_height: usize,
 		rng_seed: Seed,
 	) -> Result<Self, Error> {
-		// Group extrinsics by app id, also sorted by app id.
-		// Using a BTreeMap here will still iter in sorted order. Sweet!
-		let grouped = extrinsics.iter().fold::<BTreeMap<AppId, Vec<&[u8]>>, _>(
-			BTreeMap::default(),
-			|mut acc, e| {
-				acc.entry(e.app_id).or_default().push(e.data.as_slice());
-				acc
-			},
-		);
+		let max_rows = BlockLengthRows(u32::try_from(max_height).unwrap_or(u32::MAX));
+		let max_cols = BlockLengthColumns(u32::try_from(max_width).unwrap_or(u32::MAX));
+		let layout = LayoutProfile::Gridgen { min_width }.layout::<SCALAR_SIZE>(
+			&extrinsics,
+			max_rows,
+			max_cols,
+			rng_seed,
+		)?;
+		Self::from_layout(layout)
+	}
 
-		// Convert each group of extrinsics into scalars
-		let scalars_by_app = grouped
-			.into_iter()
-			.map(|(id, datas)| {
-				let enc = datas.encode();
-				enc.chunks(DATA_CHUNK_SIZE)
-					.map(pad_to_bls_scalar)
-					.collect::<Result<Vec<_>, _>>()
-					.map(|scalars| (id, scalars))
-			})
-			.collect::<Result<Vec<_>, _>>()?;
+	/// Creates the data grid of Scalars of an already laid out block.
+	pub fn from_layout(layout: GridLayout) -> Result<Self, Error> {
+		let lookup = DataLookup::from_id_and_len_iter(layout.xts_layout.into_iter())?;
+		let rows = usize::try_from(layout.dims.rows().0)?;
+		let cols = usize::try_from(layout.dims.cols().0)?;
+		ensure!(
+			Some(layout.chunks.len()) == rows.checked_mul(cols),
+			Error::DimensionsMismatch
+		);
 
-		let len_by_app = scalars_by_app
+		let scalars = layout
+			.chunks
 			.iter()
-			.map(|(app, scalars)| (*app, scalars.len()));
-
-		// make the index of app info
-		let lookup = DataLookup::from_id_and_len_iter(len_by_app)?;
-		let grid_size = usize::try_from(lookup.len())?;
-		let (rows, cols): (usize, usize) =
-			get_block_dims(grid_size, min_width, max_width, max_height)?.into();
-
-		let mut rng = ChaChaRng::from_seed(rng_seed);
-		// Flatten the grid
-		let grid = scalars_by_app
-			.into_iter()
-			.flat_map(|(_, scalars)| scalars)
-			.chain(iter::repeat(0).map(|_| {
-				let rnd_values: [u8; SCALAR_SIZE - 1] = rng.gen();
-				pad_to_bls_scalar(rnd_values).expect("less than SCALAR_SIZE values, can't fail")
-			}));
-
-		let row_major_evals = DMatrix::from_row_iterator(rows, cols, grid);
+			.map(pad_to_bls_scalar)
+			.collect::<Result<Vec<_>, _>>()?;
+		let row_major_evals = DMatrix::from_row_iterator(rows, cols, scalars);
 
 		Ok(EvaluationGrid {
 			lookup,
@@ -233,6 +218,52 @@ impl EvaluationGrid {
 		})
 	}
 
+	/// Extends every row of the grid by `col_factor`, so the resulting grid has
+	/// `cols * col_factor` columns. Original evaluations are kept on columns which are a multiple
+	/// of `col_factor`.
+	pub fn extend_rows(&self, col_factor: NonZeroU16) -> Result<Self, Error> {
+		let dims = self.dims();
+		let (new_rows, new_cols): (usize, usize) = dims
+			.extend(unsafe { NonZeroU16::new_unchecked(1) }, col_factor)
+			.ok_or(Error::CellLengthExceeded)?
+			.into();
+		let (_rows, cols): (usize, usize) = dims.into();
+
+		let domain =
+			GeneralEvaluationDomain::<ArkScalar>::new(cols).ok_or(Error::DomainSizeInvalid)?;
+		let domain_new =
+			GeneralEvaluationDomain::<ArkScalar>::new(new_cols).ok_or(Error::DomainSizeInvalid)?;
+		ensure!(domain_new.size() == new_cols, Error::DomainSizeInvalid);
+
+		let new_data = self.evals.row_iter().flat_map(|row| {
+			let mut row = row.iter().cloned().collect::<Vec<_>>();
+			domain.ifft_in_place(&mut row);
+			domain_new.fft_in_place(&mut row);
+			row
+		});
+
+		let row_major_evals = DMatrix::from_row_iterator(new_rows, new_cols, new_data);
+		debug_assert!(row_major_evals.shape() == (new_rows, new_cols));
+		Ok(Self {
+			lookup: self.lookup.clone(),
+			evals: row_major_evals,
+		})
+	}
+
+	/// Two-dimensional Reed-Solomon extension: columns are extended by `row_factor` and then
+	/// rows are extended by `col_factor`.
+	///
+	/// Row polynomials of the extended grid keep the degree of the original rows, so their
+	/// commitments match the ones of a grid extended only by `extend_columns`.
+	pub fn extend(&self, row_factor: NonZeroU16, col_factor: NonZeroU16) -> Result<Self, Error> {
+		self.extend_columns(row_factor)?.extend_rows(col_factor)
+	}
+
+	/// Extends the grid using the factors of `extension`.
+	pub fn extend_with(&self, extension: ExtensionConfig) -> Result<Self, Error> {
+		self.extend(extension.row_factor(), extension.col_factor())
+	}
+
 	pub fn make_polynomial_grid(&self) -> Result<PolynomialGrid, Error> {
 		let (_rows, cols): (usize, usize) = self.evals.shape();
 		let domain =
@@ -321,6 +352,70 @@ impl PolynomialGrid {
 		Ok(KZGProof::open(srs, witness)?)
 	}
 
+	/// Computes the proof of `cell` like [`Self::proof`], committing to the witness polynomial
+	/// with `public_params` (over its precomputed table with the `precomputed-srs` feature).
+	pub fn proof_with_public_params(
+		&self,
+		public_params: &PublicParams,
+		cell: &Cell,
+	) -> Result<Proof<Bls12_381>, Error> {
+		let x = cell.col.0 as usize;
+		let y = cell.row.0 as usize;
+		let poly = self.inner.get(y).ok_or(Error::CellLengthExceeded)?.clone();
+		let point = *self.points.get(x).ok_or(Error::CellLengthExceeded)?;
+		Ok(public_params.open(poly, point)?)
+	}
+
+	/// Computes the proofs of every cell of the grid using the Feist-Khovratovich technique.
+	/// Proofs of all the cells of a row are computed at once with `O(n log n)` group operations,
+	/// instead of one witness polynomial and MSM per cell.
+	pub fn all_proofs<M: MSMEngine<E = Bls12_381>>(
+		&self,
+		srs: &M1NoPrecomp<Bls12_381, M>,
+	) -> Result<AllProofs, Error> {
+		let n = self.points.len();
+		let domain =
+			GeneralEvaluationDomain::<ArkScalar>::new(n).ok_or(Error::DomainSizeInvalid)?;
+		let ext_n = n.checked_mul(2).ok_or(Error::DomainSizeInvalid)?;
+		let ext_domain =
+			GeneralEvaluationDomain::<ArkScalar>::new(ext_n).ok_or(Error::DomainSizeInvalid)?;
+		ensure!(
+			self.inner.iter().all(|poly| poly.len() <= n),
+			Error::DomainSizeInvalid
+		);
+
+		// Constant polynomials are opened by the identity point
+		if n < 2 {
+			let identity = Proof(G1::zero().into());
+			let proofs = self
+				.inner
+				.iter()
+				.map(|_| vec![identity.clone(); n])
+				.collect();
+			return Ok(AllProofs { proofs });
+		}
+
+		// FFT of the Toeplitz vector `[s^(n-2)], ..., [s^0]`, shared by all rows
+		let toeplitz_len = n.checked_sub(1).ok_or(Error::DomainSizeInvalid)?;
+		let powers = srs
+			.powers_of_g1
+			.get(..toeplitz_len)
+			.ok_or(Error::PublicParamsDegreeTooLow)?;
+		let mut srs_fft = powers
+			.iter()
+			.rev()
+			.map(|p| G1::from(*p))
+			.collect::<Vec<_>>();
+		srs_fft.resize(ext_n, G1::zero());
+		ext_domain.fft_in_place(&mut srs_fft);
+
+		let proofs = cfg_iter!(self.inner)
+			.map(|poly| fk20_row_proofs(poly, &srs_fft, domain, ext_domain))
+			.collect::<Vec<_>>();
+
+		Ok(AllProofs { proofs })
+	}
+
 	pub fn multiproof<E: Pairing, M: MSMEngine<E = E>>(
 		&self,
 		srs: &M1NoPrecomp<E, M>,
@@ -374,6 +469,108 @@ pub struct Multiproof<E: Pairing> {
 	pub block: CellBlock,
 }
 
+type G1 = <Bls12_381 as Pairing>::G1;
+
+/// Proofs of all the `n` columns of a row of degree `< n`: the Toeplitz product
+/// `h_i = f_(i+1) * [s^0] + ... + f_(n-1) * [s^(n-i-2)]` is computed through a circulant
+/// embedding of size `2n`, then `h` is evaluated over the row domain.
+#[allow(clippy::arithmetic_side_effects)]
+fn fk20_row_proofs(
+	poly: &[ArkScalar],
+	srs_fft: &[G1],
+	domain: GeneralEvaluationDomain<ArkScalar>,
+	ext_domain: GeneralEvaluationDomain<ArkScalar>,
+) -> Vec<Proof<Bls12_381>> {
+	let n = domain.size();
+	let coeff = |i: usize| poly.get(i).copied().unwrap_or_else(ArkScalar::zero);
+
+	let mut toeplitz = vec![ArkScalar::zero(); 2 * n];
+	toeplitz[0] = coeff(n - 1);
+	for i in 1..n - 1 {
+		toeplitz[n + 1 + i] = coeff(i);
+	}
+	ext_domain.fft_in_place(&mut toeplitz);
+
+	let mut h = srs_fft
+		.iter()
+		.zip(toeplitz.iter())
+		.map(|(point, scalar)| *point * scalar)
+		.collect::<Vec<_>>();
+	ext_domain.ifft_in_place(&mut h);
+	h.truncate(n);
+	domain.fft_in_place(&mut h);
+
+	h.into_iter().map(|point| Proof(point.into())).collect()
+}
+
+/// Proofs of every cell of a grid, computed by [`PolynomialGrid::all_proofs`].
+#[derive(Debug, Clone)]
+pub struct AllProofs {
+	proofs: Vec<Vec<Proof<Bls12_381>>>,
+}
+
+impl AllProofs {
+	pub fn proof(&self, cell: &Cell) -> Option<&Proof<Bls12_381>> {
+		let (row, col) = cell.get_dimensions().ok()?;
+		self.proofs.get(row)?.get(col)
+	}
+
+	pub fn row(&self, row: usize) -> Option<&[Proof<Bls12_381>]> {
+		self.proofs.get(row).map(Vec::as_slice)
+	}
+}
+
+/// Keeps the proofs of the latest `capacity` blocks, so serving cell proofs of those blocks
+/// is a lookup. The least recently used block is evicted first.
+pub struct ProofCache<K> {
+	capacity: NonZeroUsize,
+	entries: VecDeque<(K, Arc<AllProofs>)>,
+}
+
+impl<K: PartialEq> ProofCache<K> {
+	pub fn new(capacity: NonZeroUsize) -> Self {
+		Self {
+			capacity,
+			entries: VecDeque::with_capacity(capacity.get()),
+		}
+	}
+
+	/// Returns the cached proofs of block `key`, if any.
+	pub fn get(&mut self, key: &K) -> Option<Arc<AllProofs>> {
+		let index = self.entries.iter().position(|(k, _)| k == key)?;
+		let entry = self.entries.remove(index)?;
+		let proofs = Arc::clone(&entry.1);
+		self.entries.push_front(entry);
+		Some(proofs)
+	}
+
+	/// Returns the cached proofs of block `key`, computing and caching them on a miss.
+	pub fn get_or_try_insert_with(
+		&mut self,
+		key: K,
+		compute: impl FnOnce() -> Result<AllProofs, Error>,
+	) -> Result<Arc<AllProofs>, Error> {
+		if let Some(proofs) = self.get(&key) {
+			return Ok(proofs);
+		}
+
+		let proofs = Arc::new(compute()?);
+		if self.entries.len() >= self.capacity.get() {
+			self.entries.pop_back();
+		}
+		self.entries.push_front((key, Arc::clone(&proofs)));
+		Ok(proofs)
+	}
+
+	pub fn len(&self) -> usize {
+		self.entries.len()
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.entries.is_empty()
+	}
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct CellBlock {
 	pub start_x: usize,
@@ -1,13 +1,16 @@
 use super::*;
 use crate::{com::Cell, couscous, gridgen::core::*, Seed};
-use avail_core::{AppExtrinsic, BlockLengthColumns, BlockLengthRows};
-use core::num::NonZeroU16;
+use avail_core::{kate_commitment::v4, AppExtrinsic, AppId, BlockLengthColumns, BlockLengthRows};
+use core::num::{NonZeroU16, NonZeroUsize};
 use hex_literal::hex;
 use kate_recovery::{
-	commitments::verify_equality,
-	matrix::{Dimensions, Position},
+	commitments::{extend_v4, verify_equality},
+	data::GCellBlock,
+	matrix::{Dimensions, ExtensionConfig, Position},
+	proof::{verify_batch, verify_multi_proof_sync, Error as ProofError},
 	testnet,
 };
+use std::sync::Arc;
 use test_case::test_case;
 
 #[test]
@@ -185,3 +188,232 @@ fn test_zero_deg_poly_commit(row_values: Vec<u8>) {
 		assert!(verification.unwrap())
 	}
 }
+
+#[test]
+fn two_dimensional_extension() {
+	let xts = vec![AppExtrinsic::from(vec![7u8; 31 * 6])];
+	let grid = EvaluationGrid::from_extrinsics(xts, 4, 4, 4, Seed::default()).unwrap();
+	let row_factor = unsafe { NonZeroU16::new_unchecked(2) };
+	let col_factor = unsafe { NonZeroU16::new_unchecked(2) };
+
+	let col_ext = grid.extend_columns(row_factor).unwrap();
+	let ext = grid.extend(row_factor, col_factor).unwrap();
+	let (rows, cols): (usize, usize) = col_ext.dims().into();
+	assert_eq!(ext.dims(), Dimensions::new_from(rows, cols * 2).unwrap());
+
+	// Column-extended evaluations stay on the even columns.
+	for y in 0..rows {
+		for x in 0..cols {
+			assert_eq!(ext.get(y, 2 * x), col_ext.get(y, x));
+		}
+	}
+
+	// Row polynomials keep their degree, so commitments match the column-only extension.
+	let commitments = |g: &EvaluationGrid| {
+		g.make_polynomial_grid()
+			.unwrap()
+			.commitments(&*PMP)
+			.unwrap()
+			.into_iter()
+			.map(|c| c.to_bytes().unwrap())
+			.collect::<Vec<_>>()
+	};
+	let commits = commitments(&ext);
+	assert_eq!(commits, commitments(&col_ext));
+
+	// Cells on the extended columns can be proven against the same commitments.
+	let extension = ExtensionConfig::new(row_factor.get(), col_factor.get()).unwrap();
+	let dims = grid.dims().with_extension(extension).unwrap();
+	let polys = ext.make_polynomial_grid().unwrap();
+	for (y, x) in [(0, 1), (1, 3), (rows - 1, 2 * cols - 1)] {
+		let cell = Cell::new(BlockLengthRows(y as u32), BlockLengthColumns(x as u32));
+		let proof = polys.proof(&*PMP, &cell).unwrap().to_bytes().unwrap();
+		let data = ext.get(y, x).unwrap().to_bytes().unwrap();
+		let cell = kate_recovery::data::SingleCell {
+			position: Position::from((y as u32, x as u16)),
+			content: [&proof[..], &data[..]].concat().try_into().unwrap(),
+		};
+		let verified = kate_recovery::proof::verify_v2(&PMP, dims, &commits[y], &cell).unwrap();
+		assert!(verified);
+	}
+
+	// A V4 header only holds the commitments of the original rows, the ones of the extended
+	// rows are derived from them.
+	let original = commitments(&grid);
+	let kate_commitment = v4::KateCommitment::new(
+		original.len() as u16,
+		cols as u16,
+		row_factor.get(),
+		col_factor.get(),
+		Default::default(),
+		original.concat(),
+	);
+	let derived = extend_v4(&kate_commitment).unwrap();
+	assert_eq!(derived, commits);
+	let extended = grid
+		.make_polynomial_grid()
+		.unwrap()
+		.extended_commitments(&*PMP, row_factor.get().into())
+		.unwrap()
+		.into_iter()
+		.map(|c| c.to_bytes().unwrap())
+		.collect::<Vec<_>>();
+	assert_eq!(derived, extended);
+}
+
+#[test]
+fn verify_batch_on_2d_extended_grid() {
+	let xts = vec![
+		AppExtrinsic::new(AppId(1), vec![3u8; 31 * 10]),
+		AppExtrinsic::new(AppId(2), vec![5u8; 31 * 5]),
+	];
+	let grid = EvaluationGrid::from_extrinsics(xts, 4, 8, 8, Seed::default()).unwrap();
+	let extension = ExtensionConfig::new(2, 2).unwrap();
+	let ext = grid.extend_with(extension).unwrap();
+	let dims = grid.dims().with_extension(extension).unwrap();
+	assert_eq!(
+		ext.dims(),
+		Dimensions::new(dims.extended_rows() as u16, dims.extended_cols()).unwrap()
+	);
+
+	let polys = ext.make_polynomial_grid().unwrap();
+	let commitments = polys
+		.commitments(&*PMP)
+		.unwrap()
+		.into_iter()
+		.map(|c| c.to_bytes().unwrap())
+		.collect::<Vec<_>>();
+
+	// Every cell of the odd rows and columns, which are all extended.
+	let (rows, cols): (usize, usize) = ext.dims().into();
+	let cells = (1..rows)
+		.step_by(2)
+		.flat_map(|y| (1..cols).step_by(2).map(move |x| (y, x)))
+		.map(|(y, x)| {
+			let cell = Cell::new(BlockLengthRows(y as u32), BlockLengthColumns(x as u32));
+			let proof = polys.proof(&*PMP, &cell).unwrap().to_bytes().unwrap();
+			let data = ext.get(y, x).unwrap().to_bytes().unwrap();
+			kate_recovery::data::SingleCell {
+				position: Position::from((y as u32, x as u16)),
+				content: [&proof[..], &data[..]].concat().try_into().unwrap(),
+			}
+		})
+		.collect::<Vec<_>>();
+	assert!(cells
+		.iter()
+		.all(|cell| cell.position.is_extended_with(extension)));
+
+	let verification = verify_batch(&PMP, dims, &commitments, &cells).unwrap();
+	assert!(verification.is_valid());
+	assert_eq!(verification.verified, cells.len());
+
+	// Without the extension, extended columns are out of the domain.
+	assert!(matches!(
+		verify_batch(&PMP, grid.dims(), &commitments, &cells),
+		Err(ProofError::InvalidPositionInDomain)
+	));
+}
+
+#[test]
+fn verify_multi_proof_sync_blocks() {
+	let xts = vec![
+		AppExtrinsic::new(AppId(1), vec![3u8; 31 * 20]),
+		AppExtrinsic::new(AppId(2), vec![5u8; 31 * 9]),
+	];
+	let grid = EvaluationGrid::from_extrinsics(xts, 4, 16, 16, Seed::default())
+		.unwrap()
+		.extend_columns(unsafe { NonZeroU16::new_unchecked(2) })
+		.unwrap();
+	let polys = grid.make_polynomial_grid().unwrap();
+	let (_, cols): (usize, usize) = grid.dims().into();
+	let commitments = polys
+		.commitments(&*PMP)
+		.unwrap()
+		.iter()
+		.flat_map(|c| c.to_bytes().unwrap())
+		.collect::<Vec<_>>();
+
+	let target_dims = Dimensions::new_from(2, 2).unwrap();
+	let mut proofs = [(0, 0), (1, 0), (1, 1)]
+		.into_iter()
+		.map(|(x, y)| {
+			let cell = Cell::new(BlockLengthRows(y), BlockLengthColumns(x));
+			let mp = polys.multiproof(&*PMP, &cell, &grid, target_dims).unwrap();
+			let evals = mp
+				.evals
+				.iter()
+				.flatten()
+				.map(|eval| eval.to_bytes().unwrap())
+				.collect::<Vec<_>>();
+			let block = GCellBlock {
+				start_x: mp.block.start_x as u32,
+				start_y: mp.block.start_y as u32,
+				end_x: mp.block.end_x as u32,
+				end_y: mp.block.end_y as u32,
+			};
+			((evals, mp.proof.to_bytes().unwrap()), block)
+		})
+		.collect::<Vec<_>>();
+
+	let verified = verify_multi_proof_sync(&PMP, &proofs, &commitments, cols).unwrap();
+	assert_eq!(verified, vec![true, true, true]);
+
+	// Only the block with a tampered evaluation fails
+	let ((evals, _), _) = &mut proofs[1];
+	evals[0] = ArkScalar::from(7u64).to_bytes().unwrap();
+	let verified = verify_multi_proof_sync(&PMP, &proofs, &commitments, cols).unwrap();
+	assert_eq!(verified, vec![true, false, true]);
+
+	let truncated = &commitments[..commitments.len() - 1];
+	let res = verify_multi_proof_sync(&PMP, &proofs, truncated, cols);
+	assert!(matches!(res, Err(ProofError::InvalidCommitmentsLength)));
+
+	let ((evals, _), _) = &mut proofs[2];
+	let found = evals.len() - 1;
+	evals.pop();
+	let res = verify_multi_proof_sync(&PMP, &proofs, &commitments, cols);
+	assert!(matches!(res, Err(ProofError::InvalidEvalsLength { found: f, .. }) if f == found));
+}
+
+#[test]
+fn all_proofs_match_single_proofs() {
+	let xts = vec![
+		AppExtrinsic::new(AppId(1), (0..=250).collect()),
+		AppExtrinsic::new(AppId(2), vec![9u8; 31 * 5]),
+	];
+	let grid = EvaluationGrid::from_extrinsics(xts, 4, 16, 16, Seed::default())
+		.unwrap()
+		.extend_columns(unsafe { NonZeroU16::new_unchecked(2) })
+		.unwrap();
+	let polys = grid.make_polynomial_grid().unwrap();
+	let (rows, cols): (usize, usize) = grid.dims().into();
+
+	let public_params = couscous::PublicParams::new(testnet::multiproof_params(256, 256));
+	let mut cache = ProofCache::new(NonZeroUsize::new(1).unwrap());
+	let all_proofs = cache
+		.get_or_try_insert_with([1u8; 32], || polys.all_proofs(&*PMP))
+		.unwrap();
+	for y in 0..rows {
+		for x in 0..cols {
+			let cell = Cell::new(BlockLengthRows(y as u32), BlockLengthColumns(x as u32));
+			let expected = polys.proof(&*PMP, &cell).unwrap();
+			let proof = all_proofs.proof(&cell).unwrap();
+			assert_eq!(proof.to_bytes().unwrap(), expected.to_bytes().unwrap());
+			let proof = polys
+				.proof_with_public_params(&public_params, &cell)
+				.unwrap();
+			assert_eq!(proof.to_bytes().unwrap(), expected.to_bytes().unwrap());
+		}
+	}
+
+	// Cached proofs are served without recomputing them, until evicted by another block
+	let cached = cache
+		.get_or_try_insert_with([1u8; 32], || unreachable!())
+		.unwrap();
+	assert!(Arc::ptr_eq(&cached, &all_proofs));
+	cache
+		.get_or_try_insert_with([2u8; 32], || polys.all_proofs(&*PMP))
+		.unwrap();
+	assert_eq!(cache.len(), 1);
+	assert!(cache.get(&[1u8; 32]).is_none());
+}
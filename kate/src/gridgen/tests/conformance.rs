@@ -0,0 +1,162 @@
+use super::*;
+use crate::{
+	com::{par_build_commitments, par_extend_data_matrix},
+	couscous,
+	gridgen::core::{AsBytes, SCALAR_SIZE},
+	layout::{GridLayout, LayoutProfile},
+	metrics::IgnoreMetrics,
+	M1NoPrecomp, Seed,
+};
+use avail_core::{BlockLengthColumns, BlockLengthRows, DataLookup};
+use hex_literal::hex;
+use kate_recovery::matrix::ExtensionConfig;
+
+const MAX_ROWS: BlockLengthRows = BlockLengthRows(256);
+const MAX_COLS: BlockLengthColumns = BlockLengthColumns(32);
+const EXTENSION: ExtensionConfig = ExtensionConfig::DEFAULT;
+
+fn flatten(layout: &GridLayout) -> Vec<u8> {
+	layout
+		.chunks
+		.iter()
+		.flat_map(|chunk| {
+			let mut cell = chunk.to_vec();
+			cell.resize(SCALAR_SIZE, 0);
+			cell
+		})
+		.collect()
+}
+
+fn commitment_bytes(grid: &EvaluationGrid, srs: &M1NoPrecomp) -> Vec<u8> {
+	grid.make_polynomial_grid()
+		.unwrap()
+		.commitments(srs)
+		.unwrap()
+		.iter()
+		.flat_map(|c| c.to_bytes().unwrap())
+		.collect()
+}
+
+/// Checks that the `com` and `gridgen` pipelines extend and commit to `layout` identically.
+fn assert_pipelines_match(layout: GridLayout, srs: &M1NoPrecomp) -> Result<(), TestCaseError> {
+	let com_matrix =
+		par_extend_data_matrix(layout.dims, EXTENSION, &flatten(&layout), &IgnoreMetrics {})
+			.unwrap();
+
+	let grid = EvaluationGrid::from_layout(layout).unwrap();
+	let extended = grid.extend_with(EXTENSION).unwrap();
+	prop_assert_eq!(&extended.evals, &com_matrix);
+
+	let fft_extended = grid
+		.make_polynomial_grid()
+		.unwrap()
+		.extended_commitments(srs, EXTENSION.row_factor().get().into())
+		.unwrap()
+		.iter()
+		.flat_map(|c| c.to_bytes().unwrap())
+		.collect::<Vec<_>>();
+	prop_assert_eq!(commitment_bytes(&extended, srs), fft_extended);
+	Ok(())
+}
+
+/// Checks that `from_extrinsics` and the `Gridgen` profile build the grid which
+/// `from_extrinsics` built before sharing its layout with `com`, given as row-major `evals`.
+fn assert_gridgen_golden(
+	xts: Vec<AppExtrinsic>,
+	max_cols: usize,
+	seed: Seed,
+	lookup: &[(u32, u32)],
+	dims: (usize, usize),
+	evals: &[[u8; SCALAR_SIZE]],
+) {
+	let lookup = DataLookup::from_id_and_len_iter(lookup.iter().copied()).unwrap();
+	let max_rows = BlockLengthRows(8);
+	let max_cols_len = BlockLengthColumns(max_cols.try_into().unwrap());
+
+	let profile = LayoutProfile::Gridgen { min_width: 4 };
+	let layout = profile
+		.layout::<SCALAR_SIZE>(&xts, max_rows, max_cols_len, seed)
+		.unwrap();
+	let from_layout = EvaluationGrid::from_layout(layout).unwrap();
+	let from_extrinsics = EvaluationGrid::from_extrinsics(xts, 4, max_cols, 8, seed).unwrap();
+
+	for grid in [from_extrinsics, from_layout] {
+		assert_eq!(grid.lookup(), &lookup);
+		assert_eq!(<(usize, usize)>::from(grid.dims()), dims);
+		let grid_evals = (0..dims.0)
+			.flat_map(|y| grid.row(y).unwrap())
+			.map(|eval| eval.to_bytes().unwrap())
+			.collect::<Vec<_>>();
+		assert_eq!(grid_evals, evals);
+	}
+}
+
+#[test]
+fn gridgen_profile_matches_golden_grids() {
+	// Extrinsics are grouped by application, in submission order, and padded with random scalars.
+	let xts = vec![
+		AppExtrinsic::new(AppId(2), b"avail data availability".to_vec()),
+		AppExtrinsic::new(AppId(0), vec![0xab; 40]),
+		AppExtrinsic::new(AppId(2), (0u8..50).collect()),
+	];
+	assert_gridgen_golden(
+		xts,
+		4,
+		Seed::default(),
+		&[(0, 2), (2, 3)],
+		(2, 4),
+		&[
+			hex!("04a0ababababababababababababababababababababababababababababab00"),
+			hex!("ababababababababababab000000000000000000000000000000000000000000"),
+			hex!("085c617661696c206461746120617661696c6162696c697479c8000102030400"),
+			hex!("05060708090a0b0c0d0e0f101112131415161718191a1b1c1d1e1f2021222300"),
+			hex!("2425262728292a2b2c2d2e2f3031000000000000000000000000000000000000"),
+			hex!("76a04053bda0a88bda5177b86a15c3b29f559873cb481232299cd5743151ac00"),
+			hex!("4b2d63ae198e7bb0a9011f28e473c95f4013d7d53ec5fbc3b42df8ed101f6d00"),
+			hex!("e831e52bfb76e51cca8b4e9016838657edfae09cb9a71eb219025c4c87a67c00"),
+		],
+	);
+
+	// Single row grids are at least `min_width` wide.
+	let xts = vec![AppExtrinsic::new(AppId(1), b"one row".to_vec())];
+	assert_gridgen_golden(
+		xts,
+		32,
+		[7u8; 32],
+		&[(1, 1)],
+		(1, 4),
+		&[
+			hex!("041c6f6e6520726f770000000000000000000000000000000000000000000000"),
+			hex!("f45714ac8c14320745b9ab920320d8743760c0c9b611eabbde53f3fbf5efbd00"),
+			hex!("5ec2186153b880269dc807d34d20da97da779c297250353d53e70e118486a100"),
+			hex!("d3ba63e5aed234458b39597f731c684240d9d7c2998d0260de35a0a2333b9500"),
+		],
+	);
+}
+
+proptest! {
+	#![proptest_config(ProptestConfig::with_cases(10))]
+	#[test]
+	fn com_profile_matches_par_build_commitments(ref xts in app_extrinsics_strategy()) {
+		let srs = couscous::multiproof_params();
+		let (xts_layout, commitments, dims, ext_matrix) = par_build_commitments::<SCALAR_SIZE, _>(MAX_ROWS, MAX_COLS, xts, Seed::default(), &IgnoreMetrics {}).unwrap();
+
+		let layout = LayoutProfile::Com.layout::<SCALAR_SIZE>(xts, MAX_ROWS, MAX_COLS, Seed::default()).unwrap();
+		prop_assert_eq!(&layout.xts_layout, &xts_layout);
+		prop_assert_eq!(layout.dims, dims);
+
+		let grid = EvaluationGrid::from_layout(layout).unwrap();
+		prop_assert_eq!(grid.lookup(), &DataLookup::from_id_and_len_iter(xts_layout.into_iter()).unwrap());
+		let extended = grid.extend_with(EXTENSION).unwrap();
+		prop_assert_eq!(&extended.evals, &ext_matrix);
+		prop_assert_eq!(commitment_bytes(&extended, &srs), commitments);
+	}
+
+	#[test]
+	fn pipelines_match_for_every_profile(ref xts in app_extrinsics_strategy()) {
+		for profile in [LayoutProfile::Com, LayoutProfile::Gridgen { min_width: 4 }] {
+			let layout = profile.layout::<SCALAR_SIZE>(xts, MAX_ROWS, MAX_COLS, Seed::default()).unwrap();
+			assert_pipelines_match(layout, &PMP)?;
+		}
+	}
+}
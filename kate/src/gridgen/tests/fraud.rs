@@ -0,0 +1,89 @@
+use super::*;
+use crate::{com::Cell, gridgen::core::PolynomialGrid, Seed};
+use avail_core::{kate_commitment::v3::KateCommitment, BlockLengthColumns, BlockLengthRows};
+use core::num::NonZeroU16;
+use kate_recovery::{
+	data::SingleCell,
+	fraud::{build_bad_encoding_proof, verify_bad_encoding_proof, Error},
+	matrix::Dimensions,
+};
+
+fn column_cells(ext_grid: &EvaluationGrid, polys: &PolynomialGrid, col: usize) -> Vec<SingleCell> {
+	let (rows, _): (usize, usize) = ext_grid.dims().into();
+	(0..rows)
+		.map(|row| {
+			let cell = Cell::new(BlockLengthRows(row as u32), BlockLengthColumns(col as u32));
+			let proof = polys.proof(&*PMP, &cell).unwrap().to_bytes().unwrap();
+			let data = ext_grid.get(row, col).unwrap().to_bytes().unwrap();
+			let position = Position::from((row as u32, col as u16));
+			SingleCell::new(
+				position,
+				[&proof[..], &data[..]].concat().try_into().unwrap(),
+			)
+		})
+		.collect()
+}
+
+fn setup(tamper: bool) -> (Dimensions, KateCommitment, Vec<SingleCell>) {
+	let xts = vec![
+		AppExtrinsic::new(AppId(1), vec![1u8; 31 * 7]),
+		AppExtrinsic::new(AppId(2), (0..=200).collect()),
+	];
+	let grid = EvaluationGrid::from_extrinsics(xts, 4, 4, 16, Seed::default()).unwrap();
+	let mut ext_grid = grid
+		.extend_columns(unsafe { NonZeroU16::new_unchecked(2) })
+		.unwrap();
+	// A malicious block producer commits to an extended row which is not the column extension
+	if tamper {
+		ext_grid.evals[(1, 2)] += ArkScalar::from(1u64);
+	}
+
+	let polys = ext_grid.make_polynomial_grid().unwrap();
+	let commitments = polys
+		.commitments(&*PMP)
+		.unwrap()
+		.iter()
+		.flat_map(|c| c.to_bytes().unwrap())
+		.collect::<Vec<_>>();
+	let dims = grid.dims();
+	let commitment = KateCommitment::new(
+		dims.rows().get(),
+		dims.cols().get(),
+		Default::default(),
+		commitments,
+	);
+
+	(dims, commitment, column_cells(&ext_grid, &polys, 2))
+}
+
+#[test]
+fn bad_encoding_proof() {
+	let (dims, commitment, cells) = setup(true);
+	let commitments = kate_recovery::commitments::from_commitment(&commitment).unwrap();
+
+	let proof = build_bad_encoding_proof(&PMP, dims, &commitments, &cells)
+		.unwrap()
+		.expect("Column is not a codeword");
+	assert_eq!(proof.cells.len(), dims.height() + 1);
+	assert!(verify_bad_encoding_proof(&PMP, &commitment, &proof).unwrap());
+
+	// Proofs with forged openings are rejected
+	let mut forged = proof.clone();
+	forged.cells[0].content[48] ^= 1;
+	let res = verify_bad_encoding_proof(&PMP, &commitment, &forged);
+	assert!(matches!(res, Err(Error::InvalidOpening(_))));
+
+	let mut truncated = proof;
+	truncated.cells.pop();
+	let res = verify_bad_encoding_proof(&PMP, &commitment, &truncated);
+	assert!(matches!(res, Err(Error::InvalidCellCount(_))));
+}
+
+#[test]
+fn no_bad_encoding_proof_for_honest_block() {
+	let (dims, commitment, cells) = setup(false);
+	let commitments = kate_recovery::commitments::from_commitment(&commitment).unwrap();
+
+	let proof = build_bad_encoding_proof(&PMP, dims, &commitments, &cells).unwrap();
+	assert!(proof.is_none());
+}
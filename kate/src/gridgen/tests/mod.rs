@@ -11,7 +11,9 @@ use rand_chacha::ChaChaRng;
 use crate::{gridgen::core::EvaluationGrid, ArkScalar};
 
 mod commitments;
+mod conformance;
 mod formatting;
+mod fraud;
 mod reconstruction;
 
 pub static PMP: Lazy<M1NoPrecomp<Bls12_381, BlstMSMEngine>> =
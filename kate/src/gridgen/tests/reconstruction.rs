@@ -1,15 +1,22 @@
 use super::PMP;
 use crate::{
 	com::Cell,
-	gridgen::{core::EvaluationGrid, tests::sample_cells},
+	gridgen::{
+		core::{EvaluationGrid, SCALAR_SIZE},
+		tests::sample_cells,
+	},
+	layout::LayoutProfile,
 	Seed,
 };
 use avail_core::{AppExtrinsic, AppId, BlockLengthColumns, BlockLengthRows};
 use core::num::NonZeroU16;
 use kate_recovery::{
-	com::{reconstruct_app_extrinsics, reconstruct_extrinsics},
-	data::SingleCell as DCell,
-	matrix::{Dimensions, Position},
+	com::{
+		reconstruct_app_extrinsics, reconstruct_available, reconstruct_extrinsics, verify_padding,
+		ReconstructionError,
+	},
+	data::{DataCell, SingleCell as DCell},
+	matrix::{Dimensions, ExtensionConfig, Position},
 };
 use poly_multiproof::traits::AsBytes;
 use proptest::prelude::*;
@@ -114,3 +121,58 @@ get erasure coded to ensure redundancy."#;
 	let res_2 = reconstruct_app_extrinsics(&grid.lookup, bdims, cols_2, AppId(2)).unwrap();
 	assert_eq!(res_2[0], app_id_2_data);
 }
+
+#[test]
+fn reconstructed_padding_matches_seed() {
+	let xts = vec![
+		AppExtrinsic::new(AppId(1), vec![5; 100]),
+		AppExtrinsic::new(AppId(2), vec![6; 40]),
+	];
+	let seed = [7u8; 32];
+	for profile in [LayoutProfile::Com, LayoutProfile::Gridgen { min_width: 4 }] {
+		let layout = profile
+			.layout::<SCALAR_SIZE>(&xts, BlockLengthRows(16), BlockLengthColumns(4), seed)
+			.unwrap();
+		let grid = EvaluationGrid::from_layout(layout).unwrap();
+		let ext_grid = grid.extend_with(ExtensionConfig::DEFAULT).unwrap();
+
+		let dims = grid.dims();
+		let data = reconstruct_available(dims, sample_cells(&ext_grid, None)).unwrap();
+		assert!(verify_padding(&grid.lookup, dims, &data, seed).is_ok());
+
+		let res = verify_padding(&grid.lookup, dims, &data, Seed::default());
+		assert!(matches!(res, Err(ReconstructionError::PaddingMismatch(_))));
+	}
+}
+
+#[test]
+fn test_reconstruct_with_4x_extension() {
+	let xts = vec![
+		AppExtrinsic::new(AppId(1), vec![5; 100]),
+		AppExtrinsic::new(AppId(2), vec![6; 40]),
+	];
+	let extension = ExtensionConfig::new(4, 1).unwrap();
+	let grid = EvaluationGrid::from_extrinsics(xts.clone(), 4, 4, 16, Seed::default()).unwrap();
+	let ext_grid = grid.extend_with(extension).unwrap();
+
+	let dims = grid.dims().with_extension(extension).unwrap();
+	let (ext_rows, cols): (usize, usize) = ext_grid.dims().into();
+	assert_eq!(ext_rows, dims.extended_rows() as usize);
+
+	// Keep only a quarter of every column, none of them being an original data row.
+	let ext_grid = &ext_grid;
+	let cells = (0..cols)
+		.flat_map(|col| {
+			(0..ext_rows)
+				.filter(move |row| row % 4 == col % 3 + 1)
+				.map(move |row| {
+					let data = ext_grid.get(row, col).unwrap().to_bytes().unwrap();
+					DataCell::new(Position::from((row as u32, col as u16)), data)
+				})
+		})
+		.collect::<Vec<_>>();
+
+	let res = reconstruct_extrinsics(&grid.lookup, dims, cells).unwrap();
+	assert_eq!(res[0], (AppId(1), vec![xts[0].data.clone()]));
+	assert_eq!(res[1], (AppId(2), vec![xts[1].data.clone()]));
+}
@@ -0,0 +1,417 @@
+//! Builds the header extension of a block from its extrinsics.
+
+use crate::{
+	com::Error,
+	couscous::{self, PublicParams},
+	gridgen::core::{AsBytes, EvaluationGrid},
+	metrics::Metrics,
+	BlockDimensions, Seed, LOG_TARGET,
+};
+use avail_core::{
+	header::{
+		extension::{v3, v4, v5},
+		HeaderExtension,
+	},
+	kate_commitment, AppExtrinsic, BlockLengthColumns, BlockLengthRows, DataLookup, ErrorReason,
+	HeaderVersion, BLOCK_CHUNK_SIZE,
+};
+use kate_recovery::{commitments, matrix::ExtensionConfig};
+use primitive_types::H256;
+use std::time::Instant;
+
+/// Minimum width of the data grid.
+pub const MIN_WIDTH: usize = 4;
+
+/// Header extension of a block, along with the extended grid it commits to.
+pub struct BuiltHeaderExtension {
+	pub extension: HeaderExtension,
+	/// Grid extended as committed by the extension, or `None` for empty and faulty extensions.
+	pub grid: Option<EvaluationGrid>,
+}
+
+/// Builds header extensions of blocks up to `max_rows`x`max_cols`.
+///
+/// If the data does not fit into the grid or its commitments cannot be generated, the faulty
+/// extension of the version is built instead, recording the reason into its data lookup.
+pub struct HeaderExtensionBuilder<'a, M> {
+	max_rows: BlockLengthRows,
+	max_cols: BlockLengthColumns,
+	seed: Seed,
+	version: HeaderVersion,
+	extension: ExtensionConfig,
+	public_params: &'a PublicParams,
+	metrics: &'a M,
+}
+
+impl<'a, M: Metrics> HeaderExtensionBuilder<'a, M> {
+	pub fn new(
+		max_rows: BlockLengthRows,
+		max_cols: BlockLengthColumns,
+		seed: Seed,
+		version: HeaderVersion,
+		metrics: &'a M,
+	) -> Self {
+		Self {
+			max_rows,
+			max_cols,
+			seed,
+			version,
+			extension: ExtensionConfig::DEFAULT,
+			public_params: couscous::public_params(),
+			metrics,
+		}
+	}
+
+	/// Extends the grid of V4 extensions by `extension` instead of [`ExtensionConfig::DEFAULT`].
+	///
+	/// V3 and V5 extensions have no room for it, so they always use the default extension.
+	pub fn with_extension(mut self, extension: ExtensionConfig) -> Self {
+		self.extension = extension;
+		self
+	}
+
+	/// Commits with `public_params` instead of [`couscous::public_params`].
+	pub fn with_public_params(mut self, public_params: &'a PublicParams) -> Self {
+		self.public_params = public_params;
+		self
+	}
+
+	/// Builds the header extension of `extrinsics`, whose submitted data has `data_root`.
+	pub fn build(&self, extrinsics: Vec<AppExtrinsic>, data_root: H256) -> BuiltHeaderExtension {
+		if extrinsics.is_empty() {
+			return BuiltHeaderExtension {
+				extension: HeaderExtension::get_empty_header(data_root, self.version),
+				grid: None,
+			};
+		}
+
+		self.try_build(extrinsics, data_root)
+			.unwrap_or_else(|error| {
+				log::error!(target: LOG_TARGET, "Header extension cannot be built: {error:?}");
+				let reason = match error {
+					Error::BlockTooBig => ErrorReason::BlockTooBig,
+					_ => ErrorReason::CommitmentFailure,
+				};
+				BuiltHeaderExtension {
+					extension: faulty_header(data_root, self.version, reason),
+					grid: None,
+				}
+			})
+	}
+
+	fn try_build(
+		&self,
+		extrinsics: Vec<AppExtrinsic>,
+		data_root: H256,
+	) -> Result<BuiltHeaderExtension, Error> {
+		let start = Instant::now();
+		let grid = EvaluationGrid::from_extrinsics(
+			extrinsics,
+			MIN_WIDTH,
+			usize::try_from(self.max_cols.0)?,
+			usize::try_from(self.max_rows.0)?,
+			self.seed,
+		)?;
+		let dims = grid.dims();
+		let (rows, cols) = (dims.rows().get(), dims.cols().get());
+		let block_dims = BlockDimensions::new(
+			BlockLengthRows(rows.into()),
+			BlockLengthColumns(cols.into()),
+			BLOCK_CHUNK_SIZE,
+		)
+		.ok_or(Error::BlockTooBig)?;
+		let block_len = u32::try_from(block_dims.size()).unwrap_or(u32::MAX);
+		self.metrics.block_dims_and_size(block_dims, block_len);
+		self.metrics.preparation_block_time(start.elapsed());
+
+		let start = Instant::now();
+		let config = match self.version {
+			HeaderVersion::V4 => self.extension,
+			HeaderVersion::V3 | HeaderVersion::V5 => ExtensionConfig::DEFAULT,
+		};
+		let extended_grid = grid.extend_with(config)?;
+		self.metrics.extended_block_time(start.elapsed());
+
+		let start = Instant::now();
+		let public_params = self.public_params;
+		let polynomial_grid = grid.make_polynomial_grid()?;
+		let row_commitments = match self.version {
+			// Rows of the 2D-extended grid are committed by extending the commitments of the
+			// original rows, see `kate_recovery::commitments::extend_v4`.
+			HeaderVersion::V4 => polynomial_grid.commitments(public_params)?,
+			HeaderVersion::V3 | HeaderVersion::V5 => polynomial_grid
+				.extended_commitments(public_params, config.row_factor().get().into())?,
+		};
+		let commitment = row_commitments
+			.iter()
+			.map(|c| c.to_bytes())
+			.collect::<Result<Vec<_>, _>>()?
+			.concat();
+		self.metrics.commitment_build_time(start.elapsed());
+
+		let app_lookup = grid.lookup().clone();
+		let extension: HeaderExtension = match self.version {
+			HeaderVersion::V3 => v3::HeaderExtension {
+				app_lookup,
+				commitment: kate_commitment::v3::KateCommitment::new(
+					rows, cols, data_root, commitment,
+				),
+			}
+			.into(),
+			HeaderVersion::V4 => v4::HeaderExtension {
+				app_lookup,
+				commitment: kate_commitment::v4::KateCommitment::new(
+					rows,
+					cols,
+					config.row_factor().get(),
+					config.col_factor().get(),
+					data_root,
+					commitment,
+				),
+			}
+			.into(),
+			HeaderVersion::V5 => {
+				let commitment =
+					kate_commitment::v3::KateCommitment::new(rows, cols, data_root, commitment);
+				let app_rows =
+					v5::app_rows(&app_lookup, &commitment).map_err(|_| Error::IndexOutOfRange)?;
+				v5::HeaderExtension {
+					app_rows_root: v5::app_rows_root(&app_rows),
+					app_lookup,
+					commitment,
+				}
+				.into()
+			},
+		};
+
+		Ok(BuiltHeaderExtension {
+			extension,
+			grid: Some(extended_grid),
+		})
+	}
+}
+
+/// Faulty header extension of `version`, whose data lookup records `reason`.
+fn faulty_header(data_root: H256, version: HeaderVersion, reason: ErrorReason) -> HeaderExtension {
+	let mut extension = HeaderExtension::get_faulty_header(data_root, version);
+	let app_lookup = DataLookup::new_error_with_reason(reason);
+	match &mut extension {
+		HeaderExtension::V3(ext) => ext.app_lookup = app_lookup,
+		HeaderExtension::V4(ext) => ext.app_lookup = app_lookup,
+		HeaderExtension::V5(ext) => ext.app_lookup = app_lookup,
+	}
+	extension
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::metrics::IgnoreMetrics;
+	use avail_core::AppId;
+	use test_case::test_case;
+
+	fn extrinsics() -> Vec<AppExtrinsic> {
+		vec![
+			AppExtrinsic::new(AppId(0), vec![0u8; 100]),
+			AppExtrinsic::new(AppId(1), vec![1u8; 300]),
+			AppExtrinsic::new(AppId(3), vec![3u8; 50]),
+		]
+	}
+
+	fn build(
+		rows: u32,
+		cols: u32,
+		extrinsics: Vec<AppExtrinsic>,
+		version: HeaderVersion,
+	) -> BuiltHeaderExtension {
+		HeaderExtensionBuilder::new(
+			BlockLengthRows(rows),
+			BlockLengthColumns(cols),
+			Seed::default(),
+			version,
+			&IgnoreMetrics {},
+		)
+		.build(extrinsics, H256::repeat_byte(1))
+	}
+
+	#[test_case(HeaderVersion::V3; "v3")]
+	#[test_case(HeaderVersion::V4; "v4")]
+	#[test_case(HeaderVersion::V5; "v5")]
+	fn builds_valid_extensions(version: HeaderVersion) {
+		let built = build(64, 16, extrinsics(), version);
+		let extension = built.extension;
+		assert_eq!(extension.get_header_version(), version);
+		assert_eq!(extension.validate(), Ok(()));
+		assert_eq!(extension.data_root(), H256::repeat_byte(1));
+
+		let grid =
+			EvaluationGrid::from_extrinsics(extrinsics(), MIN_WIDTH, 16, 64, Seed::default())
+				.unwrap();
+		assert_eq!(extension.app_lookup(), grid.lookup());
+		let (rows, cols): (usize, usize) = grid.dims().into();
+		assert_eq!(usize::from(extension.rows()), rows);
+		assert_eq!(usize::from(extension.cols()), cols);
+
+		let extended_grid = built.grid.unwrap();
+		assert_eq!(
+			extended_grid.dims(),
+			grid.extend_with(ExtensionConfig::DEFAULT).unwrap().dims()
+		);
+	}
+
+	#[test]
+	fn commitments_match_extended_grid() {
+		let built = build(64, 16, extrinsics(), HeaderVersion::V3);
+		let HeaderExtension::V3(extension) = built.extension else {
+			panic!("V3 extension expected");
+		};
+
+		let grid =
+			EvaluationGrid::from_extrinsics(extrinsics(), MIN_WIDTH, 16, 64, Seed::default())
+				.unwrap();
+		let expected = grid
+			.extend_with(ExtensionConfig::DEFAULT)
+			.unwrap()
+			.make_polynomial_grid()
+			.unwrap()
+			.commitments(&couscous::multiproof_params())
+			.unwrap()
+			.into_iter()
+			.flat_map(|c| c.to_bytes().unwrap())
+			.collect::<Vec<_>>();
+		assert_eq!(extension.commitment.commitment, expected);
+	}
+
+	#[test]
+	fn commits_with_injected_public_params() {
+		let public_params = PublicParams::new(kate_recovery::testnet::multiproof_params(64, 16));
+		let built = HeaderExtensionBuilder::new(
+			BlockLengthRows(64),
+			BlockLengthColumns(16),
+			Seed::default(),
+			HeaderVersion::V3,
+			&IgnoreMetrics {},
+		)
+		.with_public_params(&public_params)
+		.build(extrinsics(), H256::repeat_byte(1));
+		let HeaderExtension::V3(extension) = built.extension else {
+			panic!("V3 extension expected");
+		};
+
+		let expected = built
+			.grid
+			.unwrap()
+			.make_polynomial_grid()
+			.unwrap()
+			.commitments(public_params.multiproof_params())
+			.unwrap()
+			.into_iter()
+			.flat_map(|c| c.to_bytes().unwrap())
+			.collect::<Vec<_>>();
+		assert_eq!(extension.commitment.commitment, expected);
+
+		let HeaderExtension::V3(default) = build(64, 16, extrinsics(), HeaderVersion::V3).extension
+		else {
+			panic!("V3 extension expected");
+		};
+		assert_ne!(
+			default.commitment.commitment,
+			extension.commitment.commitment
+		);
+	}
+
+	#[test]
+	fn v5_commits_to_app_rows() {
+		let HeaderExtension::V5(extension) =
+			build(64, 16, extrinsics(), HeaderVersion::V5).extension
+		else {
+			panic!("V5 extension expected");
+		};
+
+		let app_rows = v5::app_rows(&extension.app_lookup, &extension.commitment).unwrap();
+		let app_ids = app_rows.iter().map(|rows| rows.app_id).collect::<Vec<_>>();
+		assert_eq!(app_ids, vec![AppId(0), AppId(1), AppId(3)]);
+		assert_eq!(extension.app_rows_root, v5::app_rows_root(&app_rows));
+
+		let row_commitments = commitments::from_commitment(&extension.commitment).unwrap();
+		for (index, rows) in (0u32..).zip(&app_rows) {
+			let proof = v5::AppRowsProof::new(&app_rows, index).unwrap();
+			assert_eq!(proof.verify(extension.app_rows_root, rows), Ok(()));
+
+			let expected = (rows.start_row..rows.end_row)
+				.flat_map(|row| row_commitments[(row as usize).saturating_mul(2)])
+				.collect::<Vec<_>>();
+			assert_eq!(rows.commitments, expected);
+		}
+	}
+
+	#[test]
+	fn v4_commits_to_2d_extended_grid() {
+		let config = ExtensionConfig::new(2, 2).unwrap();
+		let built = HeaderExtensionBuilder::new(
+			BlockLengthRows(64),
+			BlockLengthColumns(16),
+			Seed::default(),
+			HeaderVersion::V4,
+			&IgnoreMetrics {},
+		)
+		.with_extension(config)
+		.build(extrinsics(), H256::repeat_byte(1));
+		let HeaderExtension::V4(extension) = built.extension else {
+			panic!("V4 extension expected");
+		};
+		assert_eq!(extension.validate(), Ok(()));
+		assert_eq!(
+			(
+				extension.commitment.row_extension,
+				extension.commitment.col_extension
+			),
+			(2, 2)
+		);
+
+		let grid =
+			EvaluationGrid::from_extrinsics(extrinsics(), MIN_WIDTH, 16, 64, Seed::default())
+				.unwrap();
+		let extended_grid = built.grid.unwrap();
+		assert_eq!(
+			extended_grid.dims(),
+			grid.extend_with(config).unwrap().dims()
+		);
+
+		// Every row of the 2D-extended grid is committed by the extension of the header.
+		let expected = extended_grid
+			.make_polynomial_grid()
+			.unwrap()
+			.commitments(&couscous::multiproof_params())
+			.unwrap()
+			.into_iter()
+			.map(|c| c.to_bytes().unwrap())
+			.collect::<Vec<_>>();
+		assert_eq!(
+			commitments::extend_v4(&extension.commitment).unwrap(),
+			expected
+		);
+	}
+
+	#[test_case(HeaderVersion::V3; "v3")]
+	#[test_case(HeaderVersion::V4; "v4")]
+	#[test_case(HeaderVersion::V5; "v5")]
+	fn empty_and_faulty_extensions(version: HeaderVersion) {
+		let data_root = H256::repeat_byte(1);
+		let built = build(64, 16, vec![], version);
+		assert_eq!(
+			built.extension,
+			HeaderExtension::get_empty_header(data_root, version)
+		);
+		assert!(built.grid.is_none());
+
+		let too_big = vec![AppExtrinsic::new(AppId(1), vec![1u8; 4096])];
+		let built = build(2, 4, too_big, version);
+		assert_eq!(
+			built.extension.app_lookup().error_reason(),
+			Some(ErrorReason::BlockTooBig)
+		);
+		assert_eq!(built.extension.validate(), Ok(()));
+		assert!(built.grid.is_none());
+	}
+}
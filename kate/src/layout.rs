@@ -0,0 +1,211 @@
+//! Layout of the extrinsics of a block into the data grid.
+//!
+//! `com` and `gridgen` historically used different rules to build the grid. Both of them are
+//! kept as [`LayoutProfile`]s, so the same data can be laid out by either one of them.
+
+use crate::{
+	com::{
+		app_extrinsics_group_by_app_id, get_block_dimensions, pad_iec_9797_1, DataChunk, Error,
+		XtsLayout,
+	},
+	gridgen::core::get_block_dims,
+	BlockDimensions, Seed,
+};
+use avail_core::{
+	constants::kate::DATA_CHUNK_SIZE, ensure, AppExtrinsic, BlockLengthColumns, BlockLengthRows,
+};
+use codec::Encode;
+use core::num::NonZeroU32;
+use rand::Rng;
+use rand_chacha::{rand_core::SeedableRng, ChaChaRng};
+
+/// Rules used to lay the extrinsics of a block out into the data grid.
+///
+/// Every profile groups the extrinsics by `AppId`, SCALE encodes the data of each application,
+/// splits it into chunks of `DATA_CHUNK_SIZE` bytes, and fills the rest of the grid with random
+/// chunks generated from the seed. Profiles differ on how the last chunk of each application is
+/// padded and on how the dimensions of the grid are chosen.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LayoutProfile {
+	/// Layout of [`crate::com::flatten_and_pad_block`]: the data of each application is padded
+	/// using IEC 9797-1, and dimensions follow [`crate::com::get_block_dimensions`].
+	Com,
+	/// Layout of [`crate::gridgen::core::EvaluationGrid::from_extrinsics`]: the last chunk of each
+	/// application is filled with zeroes, and dimensions follow
+	/// [`crate::gridgen::core::get_block_dims`], using at least `min_width` columns.
+	Gridgen { min_width: usize },
+}
+
+/// Extrinsics laid out into the data grid.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct GridLayout {
+	/// Number of chunks of each application, sorted by `AppId`.
+	pub xts_layout: XtsLayout,
+	pub dims: BlockDimensions,
+	/// Data chunks followed by the random padding, in row-major order.
+	/// There is exactly one chunk per cell of the grid.
+	pub chunks: Vec<DataChunk>,
+}
+
+impl LayoutProfile {
+	/// Lays `extrinsics` out into a grid of at most `max_rows`x`max_cols` cells of `CHUNK_SIZE`
+	/// bytes.
+	pub fn layout<const CHUNK_SIZE: usize>(
+		self,
+		extrinsics: &[AppExtrinsic],
+		max_rows: BlockLengthRows,
+		max_cols: BlockLengthColumns,
+		rng_seed: Seed,
+	) -> Result<GridLayout, Error> {
+		// Sorting is stable, so extrinsics of the same application keep their order.
+		let mut extrinsics = extrinsics.to_vec();
+		extrinsics.sort_by(|a, b| a.app_id.cmp(&b.app_id));
+
+		let mut xts_layout = Vec::new();
+		let mut chunks = Vec::new();
+		for (app_id, data) in app_extrinsics_group_by_app_id(&extrinsics) {
+			let app_chunks = self.split(data.encode());
+			let app_len = u32::try_from(app_chunks.len()).map_err(|_| Error::BlockTooBig)?;
+			xts_layout.push((app_id, app_len));
+			chunks.extend(app_chunks);
+		}
+
+		let dims = self.dims::<CHUNK_SIZE>(chunks.len(), max_rows, max_cols)?;
+		let cells = usize::try_from(dims.rows().0)?
+			.checked_mul(usize::try_from(dims.cols().0)?)
+			.ok_or(Error::BlockTooBig)?;
+		ensure!(chunks.len() <= cells, Error::BlockTooBig);
+
+		let mut rng = ChaChaRng::from_seed(rng_seed);
+		chunks.resize_with(cells, || rng.gen());
+
+		Ok(GridLayout {
+			xts_layout,
+			dims,
+			chunks,
+		})
+	}
+
+	/// Splits the encoded data of an application into chunks.
+	fn split(self, data: Vec<u8>) -> Vec<DataChunk> {
+		match self {
+			Self::Com => pad_iec_9797_1(data),
+			Self::Gridgen { .. } => data
+				.chunks(DATA_CHUNK_SIZE)
+				.map(|chunk| {
+					let mut padded = [0u8; DATA_CHUNK_SIZE];
+					padded[..chunk.len()].copy_from_slice(chunk);
+					padded
+				})
+				.collect(),
+		}
+	}
+
+	/// Dimensions of the grid holding `n_chunks` chunks of data.
+	fn dims<const CHUNK_SIZE: usize>(
+		self,
+		n_chunks: usize,
+		max_rows: BlockLengthRows,
+		max_cols: BlockLengthColumns,
+	) -> Result<BlockDimensions, Error> {
+		match self {
+			Self::Com => {
+				let block_size = n_chunks
+					.checked_mul(CHUNK_SIZE)
+					.and_then(|size| u32::try_from(size).ok())
+					.ok_or(Error::BlockTooBig)?;
+				get_block_dimensions::<CHUNK_SIZE>(block_size, max_rows, max_cols)
+			},
+			Self::Gridgen { min_width } => {
+				let dims = get_block_dims(
+					n_chunks,
+					min_width,
+					usize::try_from(max_cols.0)?,
+					usize::try_from(max_rows.0)?,
+				)?;
+				let chunk_size =
+					NonZeroU32::new(u32::try_from(CHUNK_SIZE)?).ok_or(Error::ZeroDimension)?;
+				BlockDimensions::new(
+					BlockLengthRows(dims.rows().get().into()),
+					BlockLengthColumns(dims.cols().get().into()),
+					chunk_size,
+				)
+				.ok_or(Error::BlockTooBig)
+			},
+		}
+	}
+}
+
+#[cfg(test)]
+#[allow(clippy::arithmetic_side_effects)]
+mod tests {
+	use super::*;
+	use avail_core::AppId;
+	use test_case::test_case;
+
+	fn extrinsics() -> Vec<AppExtrinsic> {
+		vec![
+			AppExtrinsic::new(AppId(2), vec![2; 40]),
+			AppExtrinsic::new(AppId(0), vec![0; 30]),
+			AppExtrinsic::new(AppId(2), vec![3; 10]),
+		]
+	}
+
+	// App 0 encodes into 32 bytes and app 2 into 53 bytes.
+	#[cfg(not(feature = "maximum-block-size"))]
+	#[test_case(LayoutProfile::Com => (vec![(AppId(0), 2), (AppId(2), 2)], 1, 4) ; "com")]
+	#[test_case(LayoutProfile::Gridgen { min_width: 4 } => (vec![(AppId(0), 2), (AppId(2), 2)], 1, 4) ; "gridgen")]
+	#[test_case(LayoutProfile::Gridgen { min_width: 8 } => (vec![(AppId(0), 2), (AppId(2), 2)], 1, 8) ; "gridgen min width")]
+	fn layout_dims(profile: LayoutProfile) -> (XtsLayout, u32, u32) {
+		let layout = profile
+			.layout::<32>(
+				&extrinsics(),
+				BlockLengthRows(4),
+				BlockLengthColumns(16),
+				Seed::default(),
+			)
+			.unwrap();
+		assert_eq!(layout.dims.size(), layout.chunks.len() * 32);
+		(
+			layout.xts_layout,
+			layout.dims.rows().0,
+			layout.dims.cols().0,
+		)
+	}
+
+	#[test]
+	fn profiles_differ_on_padding() {
+		let layout = |profile: LayoutProfile| {
+			profile
+				.layout::<32>(
+					&extrinsics(),
+					BlockLengthRows(4),
+					BlockLengthColumns(4),
+					Seed::default(),
+				)
+				.unwrap()
+		};
+		let com = layout(LayoutProfile::Com);
+		let gridgen = layout(LayoutProfile::Gridgen { min_width: 4 });
+
+		// The last byte of app 0 is followed by the IEC 9797-1 marker only on `Com`.
+		assert_eq!(com.chunks[1][..2], [0, 0x80]);
+		assert_eq!(gridgen.chunks[1][..2], [0, 0]);
+		assert_eq!(com.chunks[0], gridgen.chunks[0]);
+		assert_eq!(com.chunks[2], gridgen.chunks[2]);
+	}
+
+	#[test]
+	fn rejects_too_much_data() {
+		let xts = vec![AppExtrinsic::new(AppId(0), vec![0; 1024])];
+		for profile in [LayoutProfile::Com, LayoutProfile::Gridgen { min_width: 4 }] {
+			let layout = profile.layout::<32>(
+				&xts,
+				BlockLengthRows(4),
+				BlockLengthColumns(4),
+				Seed::default(),
+			);
+			assert!(matches!(layout, Err(Error::BlockTooBig)));
+		}
+	}
+}
@@ -3,8 +3,14 @@
 
 #[cfg(feature = "std")]
 pub mod com;
+#[cfg(feature = "precomputed-srs")]
+pub mod fixed_base;
 #[cfg(feature = "std")]
 pub mod gridgen;
+#[cfg(feature = "header-builder")]
+pub mod header_builder;
+#[cfg(feature = "std")]
+pub mod layout;
 
 pub mod couscous;
 pub mod metrics;
@@ -33,10 +39,12 @@ pub type ArkScalar = Fr;
 pub mod config {
 	use super::{BlockLengthColumns, BlockLengthRows};
 	use core::num::NonZeroU16;
+	use kate_recovery::matrix::ExtensionConfig;
 
 	pub const SCALAR_SIZE: usize = 32;
-	pub const ROW_EXTENSION: NonZeroU16 = unsafe { NonZeroU16::new_unchecked(2) };
-	pub const COL_EXTENSION: NonZeroU16 = NonZeroU16::MIN;
+	// Default extension factors, use `ExtensionConfig` for any other rate.
+	pub const ROW_EXTENSION: NonZeroU16 = ExtensionConfig::DEFAULT.row_factor();
+	pub const COL_EXTENSION: NonZeroU16 = ExtensionConfig::DEFAULT.col_factor();
 	pub const PROVER_KEY_SIZE: u32 = 48;
 	pub const PROOF_SIZE: usize = 48;
 	// MINIMUM_BLOCK_SIZE, MAX_BLOCK_ROWS and MAX_BLOCK_COLUMNS have to be a power of 2 because of the FFT functions requirements